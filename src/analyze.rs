@@ -0,0 +1,1593 @@
+//! Working with summaries: trend stats, time windows, tag filters,
+//! threshold evaluation, latency distributions, combining inputs, run
+//! overviews and the rolling statistics of a run in progress.
+
+use crate::parse::{parse_duration_ms, parse_timestamp, PointTags};
+use crate::{
+    Error, K6Summary, Metric, MetricType, TagFilter, TestOptions, Threshold, TimeWindow, TrendStat,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::str::FromStr;
+
+// =============================================================================
+// Trend Stats
+// =============================================================================
+
+/// k6's default `summaryTrendStats` plus p(99)
+pub fn default_trend_stats() -> Vec<TrendStat> {
+    vec![
+        TrendStat::Avg,
+        TrendStat::Min,
+        TrendStat::Med,
+        TrendStat::Max,
+        TrendStat::Percentile(90.0),
+        TrendStat::Percentile(95.0),
+        TrendStat::Percentile(99.0),
+    ]
+}
+
+/// Sorts stat entries: configured trend stats first, then a canonical order
+/// (avg, min, med, max, ascending percentiles, count), then alphabetically.
+pub(crate) fn sort_stats<'a>(
+    values: &'a HashMap<String, f64>,
+    trend_stats: &[TrendStat],
+) -> Vec<(&'a String, &'a f64)> {
+    let configured: Vec<String> = trend_stats.iter().map(TrendStat::key).collect();
+    let rank = |key: &str| -> (usize, f64) {
+        if let Some(idx) = configured.iter().position(|k| k == key) {
+            return (0, idx as f64);
+        }
+        match TrendStat::from_str(key) {
+            Ok(TrendStat::Avg) => (1, 0.0),
+            Ok(TrendStat::Min) => (1, 1.0),
+            Ok(TrendStat::Med) => (1, 2.0),
+            Ok(TrendStat::Max) => (1, 3.0),
+            Ok(TrendStat::Percentile(p)) => (2, p),
+            Ok(TrendStat::Count) => (3, 0.0),
+            Err(_) => (4, 0.0),
+        }
+    };
+
+    let mut sorted: Vec<(&String, &f64)> = values.iter().collect();
+    sorted.sort_by(|a, b| {
+        let (a_group, a_pos) = rank(a.0);
+        let (b_group, b_pos) = rank(b.0);
+        a_group
+            .cmp(&b_group)
+            .then_with(|| a_pos.total_cmp(&b_pos))
+            .then_with(|| a.0.cmp(b.0))
+    });
+    sorted
+}
+
+// =============================================================================
+// Time Window
+// =============================================================================
+
+/// Share of the peak `vus` a sample must reach to count as steady state
+const STEADY_STATE_SHARE: f64 = 0.9;
+
+/// A `--from`/`--to` bound
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum TimeBound {
+    /// Milliseconds after the first point, or before the last one if negative
+    Offset(f64),
+    /// Milliseconds since the epoch
+    Absolute(f64),
+}
+
+impl FromStr for TimeBound {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.contains('T') {
+            return parse_timestamp(s)
+                .map(TimeBound::Absolute)
+                .ok_or_else(|| format!("invalid timestamp '{}'", s));
+        }
+        let (sign, duration) = match s.strip_prefix('-') {
+            Some(rest) => (-1.0, rest),
+            None => (1.0, s),
+        };
+        parse_duration_ms(duration)
+            .map(|ms| TimeBound::Offset(sign * ms))
+            .ok_or_else(|| {
+                format!(
+                    "expected an offset like 30s or an RFC 3339 time, got '{}'",
+                    s
+                )
+            })
+    }
+}
+
+/// The part of the run to aggregate, from `--from`, `--to` and `--auto-steady-state`
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct WindowOptions {
+    /// Start of the window; the first point when unset
+    pub from: Option<TimeBound>,
+    /// End of the window; the last point when unset
+    pub to: Option<TimeBound>,
+    /// Restrict to the plateau of the `vus` series
+    pub steady_state: bool,
+}
+
+impl WindowOptions {
+    /// Options from the `--from`, `--to` and `--auto-steady-state` values
+    pub fn new(from: Option<TimeBound>, to: Option<TimeBound>, steady_state: bool) -> Self {
+        WindowOptions {
+            from,
+            to,
+            steady_state,
+        }
+    }
+
+    /// Whether any part of the run is left out
+    pub fn is_set(&self) -> bool {
+        self.from.is_some() || self.to.is_some() || self.steady_state
+    }
+}
+
+/// Turns window options into offsets for a run spanning `first`..`last`
+/// (epoch ms), using the `vus` series to find the steady state
+pub fn resolve_window(
+    options: &WindowOptions,
+    first: f64,
+    last: f64,
+    vus: &[(f64, f64)],
+) -> Result<Option<TimeWindow>, Error> {
+    if !options.is_set() {
+        return Ok(None);
+    }
+    let run_ms = last - first;
+
+    let mut steady_vus = None;
+    let (mut start, mut end) = (0.0, run_ms);
+    if options.steady_state {
+        let peak = vus.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+        if peak <= 0.0 {
+            return Err(Error::Window(
+                "no vus samples to find the steady state in".to_string(),
+            ));
+        }
+        let plateau = vus
+            .iter()
+            .filter(|(_, v)| *v >= peak * STEADY_STATE_SHARE)
+            .map(|(time, _)| time - first);
+        start = plateau.clone().fold(f64::INFINITY, f64::min);
+        end = plateau.fold(f64::NEG_INFINITY, f64::max);
+        steady_vus = Some(peak);
+    }
+
+    let offset = |bound: TimeBound| match bound {
+        TimeBound::Offset(ms) if ms < 0.0 => run_ms + ms,
+        TimeBound::Offset(ms) => ms,
+        TimeBound::Absolute(time) => time - first,
+    };
+    if let Some(from) = options.from {
+        start = start.max(offset(from));
+    }
+    if let Some(to) = options.to {
+        end = end.min(offset(to));
+    }
+    let (start, end) = (start.max(0.0), end.min(run_ms));
+
+    if start >= end {
+        return Err(Error::Window(format!(
+            "{} to {} holds no part of the {} run",
+            format_offset(start),
+            format_offset(end),
+            format_offset(run_ms)
+        )));
+    }
+    Ok(Some(TimeWindow {
+        start_ms: start,
+        end_ms: end,
+        run_ms,
+        steady_vus,
+    }))
+}
+
+/// Formats an offset into the run as `m:ss`, or `h:mm:ss` past the hour
+pub(crate) fn format_offset(ms: f64) -> String {
+    let secs = (ms / 1000.0).round() as u64;
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+// =============================================================================
+// Tag Filters
+// =============================================================================
+
+/// Positive filters on the same tag are alternatives; all other filters must
+/// hold. A missing tag counts as empty. Untagged points, such as the global
+/// `vus` gauge, always pass.
+pub(crate) fn matches_tag_filters(filters: &[TagFilter], tags: &impl PointTags) -> bool {
+    if filters.is_empty() || tags.is_empty() {
+        return true;
+    }
+    let matches =
+        |filter: &TagFilter| glob_match(&filter.pattern, tags.tag(&filter.key).unwrap_or(""));
+
+    filters.iter().all(|filter| {
+        if filter.negate {
+            !matches(filter)
+        } else {
+            filters
+                .iter()
+                .filter(|other| !other.negate && other.key == filter.key)
+                .any(matches)
+        }
+    })
+}
+
+/// Matches `*` (any run of characters) and `?` (any one character)
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was and how much text it has consumed so far
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// =============================================================================
+// Statistics
+// =============================================================================
+
+/// Computes a stat that was not part of the configured trend stats, e.g. a
+/// `p(99.9)` referenced only by a threshold.
+pub(crate) fn sample_stat(values: &[f64], metric_type: MetricType, stat: &str) -> Option<f64> {
+    let stat: TrendStat = stat.parse().ok()?;
+    calculate_stats(values, metric_type, &[stat], None)
+        .get(&stat.key())
+        .copied()
+}
+
+pub(crate) fn is_latency_metric(name: &str, metric_type: MetricType, contains: &str) -> bool {
+    name == "http_req_duration" || (metric_type == MetricType::Trend && contains == "time")
+}
+
+/// Aggregates raw sample values the way k6's metric sinks do. Counter rates are
+/// per second of `duration_ms`; a gauge's `value` is its last value, so gauge
+/// samples are expected in time order.
+pub fn calculate_stats(
+    values: &[f64],
+    metric_type: MetricType,
+    trend_stats: &[TrendStat],
+    duration_ms: Option<f64>,
+) -> HashMap<String, f64> {
+    let mut stats = HashMap::new();
+
+    if values.is_empty() {
+        return stats;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let sum: f64 = values.iter().sum();
+    let count = values.len() as f64;
+
+    match metric_type {
+        MetricType::Counter => {
+            stats.insert("count".to_string(), sum);
+            if let Some(ms) = duration_ms.filter(|ms| *ms > 0.0) {
+                stats.insert("rate".to_string(), sum / (ms / 1000.0));
+            }
+        }
+        MetricType::Rate => {
+            let passes = values.iter().filter(|&&v| v > 0.0).count() as f64;
+            let fails = count - passes;
+            stats.insert("rate".to_string(), passes / count);
+            stats.insert("passes".to_string(), passes);
+            stats.insert("fails".to_string(), fails);
+        }
+        MetricType::Gauge => {
+            stats.insert("value".to_string(), *values.last().unwrap_or(&0.0));
+            stats.insert("min".to_string(), *sorted.first().unwrap_or(&0.0));
+            stats.insert("max".to_string(), *sorted.last().unwrap_or(&0.0));
+        }
+        MetricType::Trend => {
+            for stat in trend_stats {
+                let value = match stat {
+                    TrendStat::Avg => sum / count,
+                    TrendStat::Min => *sorted.first().unwrap_or(&0.0),
+                    TrendStat::Max => *sorted.last().unwrap_or(&0.0),
+                    TrendStat::Med => percentile(&sorted, 50.0),
+                    TrendStat::Count => count,
+                    TrendStat::Percentile(p) => percentile(&sorted, *p),
+                };
+                stats.insert(stat.key(), value);
+            }
+        }
+    }
+
+    stats
+}
+
+/// Linearly interpolated percentile of sorted values, as k6 computes it
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+
+    let index = (p / 100.0) * (sorted.len() - 1) as f64;
+    let lower = index.floor() as usize;
+    let upper = index.ceil() as usize;
+    let fraction = index - lower as f64;
+
+    if upper >= sorted.len() {
+        sorted[sorted.len() - 1]
+    } else {
+        sorted[lower] + fraction * (sorted[upper] - sorted[lower])
+    }
+}
+
+// =============================================================================
+// Latency Distribution
+// =============================================================================
+
+pub(crate) const SPECTRUM_PERCENTILES: [f64; 9] =
+    [50.0, 75.0, 90.0, 95.0, 99.0, 99.9, 99.99, 99.999, 100.0];
+
+/// Samples between `lower` (inclusive) and `upper`
+#[non_exhaustive]
+pub struct HistogramBucket {
+    /// Lower bound, inclusive
+    pub lower: f64,
+    /// Upper bound, exclusive
+    pub upper: f64,
+    /// Samples in the bucket
+    pub count: usize,
+}
+
+/// Buckets sorted samples on a 1-2-5 logarithmic scale (e.g. 10ms, 20ms, 50ms, 100ms).
+/// NaN and infinite samples, which sort to the ends, have no bucket.
+pub fn log_histogram(sorted: &[f64]) -> Vec<HistogramBucket> {
+    // An infinite bound would keep the scale growing forever
+    let start = sorted.iter().position(|value| value.is_finite());
+    let end = sorted.iter().rposition(|value| value.is_finite());
+    let (Some(start), Some(end)) = (start, end) else {
+        return Vec::new();
+    };
+    let sorted = &sorted[start..=end];
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+
+    // Boundaries below 1µs are not meaningful for k6 timings
+    let mut lower = log_floor(min.max(0.001));
+    let mut buckets = Vec::new();
+    let mut idx = 0;
+
+    loop {
+        let upper = log_next(lower);
+        let start = idx;
+        while idx < sorted.len() && sorted[idx] < upper {
+            idx += 1;
+        }
+        buckets.push(HistogramBucket {
+            lower,
+            upper,
+            count: idx - start,
+        });
+        if upper > max {
+            break;
+        }
+        lower = upper;
+    }
+
+    buckets
+}
+
+/// Largest value of the 1-2-5 series that is <= value.
+fn log_floor(value: f64) -> f64 {
+    let decade = 10f64.powf(value.log10().floor());
+    let mantissa = value / decade;
+    // Guard against float error such as 0.3 / 0.1 = 2.9999999999999996
+    if mantissa >= 5.0 - 1e-9 {
+        5.0 * decade
+    } else if mantissa >= 2.0 - 1e-9 {
+        2.0 * decade
+    } else {
+        decade
+    }
+}
+
+fn log_next(boundary: f64) -> f64 {
+    let decade = 10f64.powf(boundary.log10().floor());
+    let mantissa = (boundary / decade).round();
+    if mantissa >= 5.0 {
+        10.0 * decade
+    } else if mantissa >= 2.0 {
+        5.0 * decade
+    } else {
+        2.0 * decade
+    }
+}
+
+/// Nearest-rank value at a percentile in [0, 1], as HdrHistogram reports it.
+/// The spectrum table uses it too, so the report and the .hgrm file agree.
+pub(crate) fn value_at_percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Renders samples in HdrHistogram's percentile distribution (.hgrm) format.
+pub fn format_hgrm(sorted: &[f64]) -> String {
+    let mut output = String::new();
+    output.push_str("       Value     Percentile TotalCount 1/(1-Percentile)\n\n");
+
+    let total = sorted.len();
+    if total == 0 {
+        return output;
+    }
+
+    // Same reporting ticks as HdrHistogram: 5 steps per halving distance to 100%
+    const TICKS_PER_HALF_DISTANCE: u32 = 5;
+    let mut half = 0;
+    'outer: loop {
+        let start = 1.0 - 0.5f64.powi(half);
+        let step = 0.5f64.powi(half + 1) / TICKS_PER_HALF_DISTANCE as f64;
+        for tick in 0..TICKS_PER_HALF_DISTANCE {
+            let p = start + step * tick as f64;
+            let count = ((p * total as f64).ceil() as usize).max(1);
+            if count >= total {
+                break 'outer;
+            }
+            output.push_str(&format!(
+                "{:12.3} {:2.12} {:10} {:14.2}\n",
+                value_at_percentile(sorted, p),
+                p,
+                count,
+                1.0 / (1.0 - p)
+            ));
+        }
+        half += 1;
+    }
+
+    let max = sorted[total - 1];
+    output.push_str(&format!("{:12.3} {:2.12} {:10}\n", max, 1.0, total));
+
+    let mean = sorted.iter().sum::<f64>() / total as f64;
+    let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / total as f64;
+    output.push_str(&format!(
+        "#[Mean    = {:12.3}, StdDeviation   = {:12.3}]\n",
+        mean,
+        variance.sqrt()
+    ));
+    output.push_str(&format!(
+        "#[Max     = {:12.3}, Total count    = {:12}]\n",
+        max, total
+    ));
+
+    output
+}
+
+/// Names of the metrics with raw latency samples, sorted
+pub fn latency_metric_names(summary: &K6Summary) -> Vec<&String> {
+    let mut names: Vec<&String> = summary.samples.keys().collect();
+    names.sort_by(|a, b| {
+        (a.as_str() != "http_req_duration")
+            .cmp(&(b.as_str() != "http_req_duration"))
+            .then_with(|| a.cmp(b))
+    });
+    names
+}
+
+// =============================================================================
+// Threshold Evaluation
+// =============================================================================
+
+/// Evaluates a k6 threshold expression such as `p(95)<500` or `rate<0.01`.
+/// Returns `None` when the expression is malformed or the stat is unavailable.
+pub fn evaluate_threshold(expr: &str, stat: impl Fn(&str) -> Option<f64>) -> Option<bool> {
+    const OPERATORS: [&str; 7] = ["===", "==", "!=", "<=", ">=", "<", ">"];
+
+    let (idx, op) = OPERATORS
+        .iter()
+        .filter_map(|op| expr.find(op).map(|idx| (idx, *op)))
+        .min_by_key(|(idx, op)| (*idx, std::cmp::Reverse(op.len())))?;
+
+    let name = expr[..idx].trim();
+    let target: f64 = expr[idx + op.len()..].trim().parse().ok()?;
+    let actual = stat(name)?;
+
+    Some(match op {
+        "<" => actual < target,
+        "<=" => actual <= target,
+        ">" => actual > target,
+        ">=" => actual >= target,
+        "!=" => actual != target,
+        _ => actual == target,
+    })
+}
+
+/// Adds thresholds that a summary's metrics don't already have, evaluated
+/// against their aggregate values
+pub fn apply_thresholds(summary: &mut K6Summary, thresholds: &BTreeMap<String, Vec<String>>) {
+    for (name, exprs) in thresholds {
+        let Some(metric) = summary.metrics.get_mut(name) else {
+            continue;
+        };
+        for expr in exprs {
+            if metric.thresholds.contains_key(expr) {
+                continue;
+            }
+            let result = evaluate_threshold(expr, |stat| metric.values.get(stat).copied());
+            let threshold = Threshold {
+                ok: result.unwrap_or(false),
+                unknown: result.is_none(),
+            };
+            metric.thresholds.insert(expr.clone(), threshold);
+        }
+    }
+}
+
+// =============================================================================
+// Script Options
+// =============================================================================
+
+/// Fills in whatever the run's own options don't provide from script options.
+pub fn merge_script_options(summary: &mut K6Summary, script: TestOptions) {
+    let Some(options) = summary.options.as_mut() else {
+        summary.options = Some(script);
+        return;
+    };
+    if options.summary_trend_stats.is_empty() {
+        options.summary_trend_stats = script.summary_trend_stats;
+    }
+    if options.summary_time_unit.is_none() {
+        options.summary_time_unit = script.summary_time_unit;
+    }
+    if options.scenarios.is_empty() && options.stages.is_empty() {
+        options.scenarios = script.scenarios;
+        options.stages = script.stages;
+        options.vus = options.vus.or(script.vus);
+        options.iterations = options.iterations.or(script.iterations);
+        options.duration = options.duration.take().or(script.duration);
+    }
+    if options.thresholds.is_empty() {
+        options.thresholds = script.thresholds;
+    }
+}
+
+// =============================================================================
+// Combining Inputs
+// =============================================================================
+
+/// Merges the summary and the points of the same run: thresholds, checks and
+/// aggregate stats come from the summary, the time-based data from the points.
+pub fn combine_inputs(mut summary: K6Summary, points: K6Summary, tolerance: f64) -> K6Summary {
+    // Windowed or filtered points cannot match the whole-run totals
+    let disagreements = match (&points.window, points.filters.is_empty()) {
+        (None, true) => compare_inputs(&summary, &points, tolerance),
+        _ => Vec::new(),
+    };
+
+    summary.samples = points.samples;
+    summary.endpoints = points.endpoints;
+    summary.timeline = points.timeline;
+    if !points.filters.is_empty() {
+        let filters: Vec<String> = points.filters.iter().map(|f| format!("`{}`", f)).collect();
+        summary.warnings.push(format!(
+            "Tag filters ({}) only apply to the point-based sections; \
+             thresholds, checks and stats cover the whole run",
+            filters.join(", ")
+        ));
+    }
+    if points.window.is_some() {
+        summary.window = points.window;
+        summary.warnings.push(
+            "The time window only applies to the point-based sections; \
+             the summary's stats cover the whole run"
+                .to_string(),
+        );
+    }
+    summary.warnings.extend(points.warnings);
+    summary.warnings.extend(disagreements);
+    summary
+}
+
+/// Compares the totals both inputs know about; `tolerance` is in percent
+fn compare_inputs(summary: &K6Summary, points: &K6Summary, tolerance: f64) -> Vec<String> {
+    let total = |metric: &Metric| match metric.metric_type {
+        MetricType::Counter => metric.values.get("count").copied(),
+        MetricType::Rate => {
+            let passes = metric.values.get("passes")?;
+            let fails = metric.values.get("fails")?;
+            Some(passes + fails)
+        }
+        _ => None,
+    };
+
+    let mut names: Vec<&String> = summary
+        .metrics
+        .keys()
+        .filter(|name| !name.contains('{') && points.metrics.contains_key(*name))
+        .collect();
+    if names.is_empty() {
+        return vec![
+            "The summary and the points share no metrics; they may be from different runs"
+                .to_string(),
+        ];
+    }
+    names.sort();
+
+    let mut warnings = Vec::new();
+    for name in names {
+        let (Some(expected), Some(actual)) =
+            (total(&summary.metrics[name]), total(&points.metrics[name]))
+        else {
+            continue;
+        };
+        let difference = if expected == 0.0 {
+            if actual == 0.0 {
+                0.0
+            } else {
+                f64::INFINITY
+            }
+        } else {
+            (actual - expected).abs() / expected * 100.0
+        };
+        if difference > tolerance {
+            warnings.push(format!(
+                "{} totals differ: {} in the summary, {} in the points ({:.1}% > {}% tolerance)",
+                name, expected, actual, difference, tolerance
+            ));
+        }
+    }
+    warnings
+}
+
+// =============================================================================
+// Run Overview
+// =============================================================================
+
+/// Outcome of all of a run's thresholds together
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Verdict {
+    /// Every threshold passed
+    Passed,
+    /// At least one threshold failed
+    Failed,
+    /// None failed, but some could not be evaluated
+    Unknown,
+}
+
+/// The headline numbers of a run, for listing runs side by side
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct RunOverview {
+    /// Test run duration in milliseconds
+    pub duration_ms: Option<f64>,
+    /// Requests per second
+    pub rps: Option<f64>,
+    /// 95th percentile request duration in milliseconds
+    pub p95: Option<f64>,
+    /// Share of failed requests
+    pub error_rate: Option<f64>,
+    /// `None` when the run has no thresholds
+    pub verdict: Option<Verdict>,
+}
+
+impl RunOverview {
+    /// The overview of one run's summary
+    pub fn of(summary: &K6Summary) -> Self {
+        let value = |metric: &str, stat: &str| {
+            summary
+                .metrics
+                .get(metric)
+                .and_then(|metric| metric.values.get(stat))
+                .copied()
+        };
+        // Only the points have samples for a p95 outside the trend stats
+        let p95 = value("http_req_duration", "p(95)").or_else(|| {
+            let samples = summary.samples.get("http_req_duration")?;
+            (!samples.is_empty()).then(|| percentile(samples, 95.0))
+        });
+
+        let thresholds: Vec<&Threshold> = summary
+            .metrics
+            .values()
+            .flat_map(|metric| metric.thresholds.values())
+            .collect();
+        let verdict = if thresholds.is_empty() {
+            None
+        } else if thresholds.iter().any(|t| !t.ok && !t.unknown) {
+            Some(Verdict::Failed)
+        } else if thresholds.iter().any(|t| t.unknown) {
+            Some(Verdict::Unknown)
+        } else {
+            Some(Verdict::Passed)
+        };
+
+        RunOverview {
+            duration_ms: summary.state.as_ref().map(|s| s.test_run_duration_ms),
+            rps: value("http_reqs", "rate"),
+            p95,
+            error_rate: value("http_req_failed", "rate"),
+            verdict,
+        }
+    }
+}
+
+// =============================================================================
+// Rolling Statistics
+// =============================================================================
+
+/// Ratio between the bounds of a histogram bucket, so live percentiles are
+/// within 1% of the exact ones
+const HISTOGRAM_GROWTH: f64 = 1.01;
+/// Keeps the bucket keys of positive values above those of negative ones
+const HISTOGRAM_OFFSET: i32 = 100_000;
+
+/// Log-scaled histogram of a trend's samples, for percentiles without keeping
+/// every sample
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl Histogram {
+    fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        *self.buckets.entry(Self::key(value)).or_default() += 1;
+    }
+
+    fn key(value: f64) -> i32 {
+        if value == 0.0 {
+            return 0;
+        }
+        let exponent = (value.abs().ln() / HISTOGRAM_GROWTH.ln()).floor() as i32;
+        (exponent + HISTOGRAM_OFFSET) * value.signum() as i32
+    }
+
+    /// The middle of a bucket on the log scale
+    fn bucket_value(key: i32) -> f64 {
+        if key == 0 {
+            return 0.0;
+        }
+        let exponent = key.abs() - HISTOGRAM_OFFSET;
+        HISTOGRAM_GROWTH.powf(exponent as f64 + 0.5) * key.signum() as f64
+    }
+
+    fn percentile(&self, p: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (p / 100.0 * (self.count - 1) as f64).round() as u64;
+        // The extremes are known exactly
+        if rank == 0 {
+            return Some(self.min);
+        }
+        if rank >= self.count - 1 {
+            return Some(self.max);
+        }
+        let mut seen = 0;
+        for (&key, &count) in &self.buckets {
+            seen += count;
+            if seen > rank {
+                return Some(Self::bucket_value(key).clamp(self.min, self.max));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// Running totals of one metric
+#[derive(Debug)]
+struct RunningMetric {
+    metric_type: MetricType,
+    count: u64,
+    sum: f64,
+    /// Non-zero samples of a rate
+    passes: u64,
+    /// Time and value of a gauge's latest sample
+    latest: Option<(f64, f64)>,
+    histogram: Histogram,
+    thresholds: Vec<String>,
+}
+
+impl RunningMetric {
+    fn new(metric_type: MetricType) -> Self {
+        RunningMetric {
+            metric_type,
+            count: 0,
+            sum: 0.0,
+            passes: 0,
+            latest: None,
+            histogram: Histogram::default(),
+            thresholds: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, time: f64, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        match self.metric_type {
+            MetricType::Rate if value != 0.0 => self.passes += 1,
+            MetricType::Gauge if self.latest.is_none_or(|(latest, _)| time >= latest) => {
+                self.latest = Some((time, value))
+            }
+            _ => {}
+        }
+        if matches!(self.metric_type, MetricType::Gauge | MetricType::Trend) {
+            self.histogram.add(value);
+        }
+    }
+
+    /// A stat as [`calculate_stats`] names it, so thresholds evaluate the same
+    fn stat(&self, stat: &str, duration_ms: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let count = self.count as f64;
+        match (self.metric_type, stat) {
+            (MetricType::Counter, "count") => Some(self.sum),
+            (MetricType::Counter, "rate") => {
+                (duration_ms > 0.0).then(|| self.sum / (duration_ms / 1000.0))
+            }
+            (MetricType::Rate, "rate") => Some(self.passes as f64 / count),
+            (MetricType::Rate, "passes") => Some(self.passes as f64),
+            (MetricType::Rate, "fails") => Some(count - self.passes as f64),
+            (MetricType::Gauge, "value") => self.latest.map(|(_, value)| value),
+            (MetricType::Gauge, "min") => Some(self.histogram.min),
+            (MetricType::Gauge, "max") => Some(self.histogram.max),
+            (MetricType::Trend, stat) => match stat.parse().ok()? {
+                TrendStat::Avg => Some(self.sum / count),
+                TrendStat::Min => Some(self.histogram.min),
+                TrendStat::Max => Some(self.histogram.max),
+                TrendStat::Med => self.histogram.percentile(50.0),
+                TrendStat::Count => Some(count),
+                TrendStat::Percentile(p) => self.histogram.percentile(p),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Where a run in progress stands, for the `k6r live` dashboard
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[non_exhaustive]
+pub struct LiveSnapshot {
+    /// Time from the first point to the latest
+    pub elapsed_ms: f64,
+    /// The latest `vus` sample
+    pub vus: Option<f64>,
+    /// Requests so far
+    pub requests: f64,
+    /// Request rate over the recent window
+    pub rps: Option<f64>,
+    /// 95th percentile request duration over the recent window
+    pub p95: Option<f64>,
+    /// Share of failed requests over the recent window
+    pub error_rate: Option<f64>,
+}
+
+/// Statistics of a run that is still being written, updated point by point.
+/// Thresholds are evaluated on every point so far, with percentiles from a
+/// histogram; the request rate, p95 and error rate cover the recent window.
+#[derive(Debug)]
+pub struct RollingStats {
+    window_ms: f64,
+    metrics: BTreeMap<String, RunningMetric>,
+    /// Threshold expressions for metrics that have not been seen yet
+    thresholds: BTreeMap<String, Vec<String>>,
+    first_time: Option<f64>,
+    last_time: Option<f64>,
+    /// Timed points of `http_reqs`, `http_req_duration` and `http_req_failed`
+    /// within the window
+    recent_requests: VecDeque<(f64, f64)>,
+    recent_durations: VecDeque<(f64, f64)>,
+    recent_failed: VecDeque<(f64, f64)>,
+}
+
+impl RollingStats {
+    /// `thresholds` are evaluated in addition to those metrics declare
+    pub fn new(window_ms: f64, thresholds: BTreeMap<String, Vec<String>>) -> Self {
+        RollingStats {
+            window_ms,
+            metrics: BTreeMap::new(),
+            thresholds,
+            first_time: None,
+            last_time: None,
+            recent_requests: VecDeque::new(),
+            recent_durations: VecDeque::new(),
+            recent_failed: VecDeque::new(),
+        }
+    }
+
+    fn metric(&mut self, name: &str, metric_type: MetricType) -> &mut RunningMetric {
+        if !self.metrics.contains_key(name) {
+            let mut metric = RunningMetric::new(metric_type);
+            metric.thresholds = self.thresholds.remove(name).unwrap_or_default();
+            self.metrics.insert(name.to_string(), metric);
+        }
+        self.metrics.get_mut(name).expect("inserted above")
+    }
+
+    /// Registers a metric definition and the thresholds it declares
+    pub fn declare(&mut self, name: &str, metric_type: MetricType, thresholds: &[String]) {
+        let metric = self.metric(name, metric_type);
+        for expr in thresholds {
+            if !metric.thresholds.contains(expr) {
+                metric.thresholds.push(expr.clone());
+            }
+        }
+    }
+
+    /// Adds a point; `metric_type` applies when the metric was not declared
+    pub fn add_point(
+        &mut self,
+        name: &str,
+        metric_type: MetricType,
+        time: Option<f64>,
+        value: f64,
+    ) {
+        if let Some(time) = time {
+            self.first_time = Some(self.first_time.map_or(time, |t| t.min(time)));
+            self.last_time = Some(self.last_time.map_or(time, |t| t.max(time)));
+        }
+        let at = time.or(self.last_time).unwrap_or(0.0);
+        self.metric(name, metric_type).add(at, value);
+
+        let (Some(time), Some(last)) = (time, self.last_time) else {
+            return;
+        };
+        let recent = match name {
+            "http_reqs" => Some(&mut self.recent_requests),
+            "http_req_duration" => Some(&mut self.recent_durations),
+            "http_req_failed" => Some(&mut self.recent_failed),
+            _ => None,
+        };
+        if let Some(recent) = recent {
+            recent.push_back((time, value));
+        }
+        // Any timed point moves the window, also once requests have stopped
+        let cutoff = last - self.window_ms;
+        for recent in [
+            &mut self.recent_requests,
+            &mut self.recent_durations,
+            &mut self.recent_failed,
+        ] {
+            while recent.front().is_some_and(|&(time, _)| time < cutoff) {
+                recent.pop_front();
+            }
+        }
+    }
+
+    fn elapsed_ms(&self) -> f64 {
+        match (self.first_time, self.last_time) {
+            (Some(first), Some(last)) => last - first,
+            _ => 0.0,
+        }
+    }
+
+    /// Where the run stands after the points so far
+    pub fn snapshot(&self) -> LiveSnapshot {
+        let elapsed_ms = self.elapsed_ms();
+        let span_ms = elapsed_ms.min(self.window_ms);
+        let requests: f64 = self.recent_requests.iter().map(|(_, value)| value).sum();
+        let mut durations: Vec<f64> = self.recent_durations.iter().map(|&(_, v)| v).collect();
+        durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let failed = self.recent_failed.iter().filter(|(_, v)| *v != 0.0).count();
+
+        LiveSnapshot {
+            elapsed_ms,
+            vus: self
+                .metrics
+                .get("vus")
+                .and_then(|vus| vus.latest)
+                .map(|(_, value)| value),
+            requests: self.metrics.get("http_reqs").map_or(0.0, |reqs| reqs.sum),
+            rps: (span_ms > 0.0).then(|| requests / (span_ms / 1000.0)),
+            p95: (!durations.is_empty()).then(|| percentile(&durations, 95.0)),
+            error_rate: (!self.recent_failed.is_empty())
+                .then(|| failed as f64 / self.recent_failed.len() as f64),
+        }
+    }
+
+    /// Every metric's values on the points so far, with the stats
+    /// [`calculate_stats`] gives its type
+    pub fn metric_values(
+        &self,
+        trend_stats: &[TrendStat],
+    ) -> Vec<(&str, MetricType, HashMap<String, f64>)> {
+        let elapsed_ms = self.elapsed_ms();
+        let mut metrics = Vec::new();
+        for (name, metric) in &self.metrics {
+            let keys = match metric.metric_type {
+                MetricType::Counter => ["count", "rate"].map(str::to_string).to_vec(),
+                MetricType::Rate => ["rate", "passes", "fails"].map(str::to_string).to_vec(),
+                MetricType::Gauge => ["value", "min", "max"].map(str::to_string).to_vec(),
+                MetricType::Trend => trend_stats.iter().map(TrendStat::key).collect(),
+            };
+            let values = keys
+                .into_iter()
+                .filter_map(|key| Some((key.clone(), metric.stat(&key, elapsed_ms)?)))
+                .collect();
+            metrics.push((name.as_str(), metric.metric_type, values));
+        }
+        metrics
+    }
+
+    /// Every threshold with its outcome on the points so far, by metric
+    pub fn thresholds(&self) -> Vec<(&str, &str, Threshold)> {
+        let elapsed_ms = self.elapsed_ms();
+        let mut thresholds = Vec::new();
+        for (name, metric) in &self.metrics {
+            for expr in &metric.thresholds {
+                let result = evaluate_threshold(expr, |stat| metric.stat(stat, elapsed_ms));
+                let threshold = Threshold {
+                    ok: result.unwrap_or(false),
+                    unknown: result.is_none(),
+                };
+                thresholds.push((name.as_str(), expr.as_str(), threshold));
+            }
+        }
+        // Metrics without points yet cannot be evaluated
+        for (name, exprs) in &self.thresholds {
+            for expr in exprs {
+                let threshold = Threshold {
+                    ok: false,
+                    unknown: true,
+                };
+                thresholds.push((name.as_str(), expr.as_str(), threshold));
+            }
+        }
+        thresholds.sort_by_key(|&(name, _, _)| name);
+        thresholds
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_jsonl, ParseOptions};
+
+    #[test]
+    fn test_percentile() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+        assert_eq!(percentile(&values, 0.0), 1.0);
+        assert_eq!(percentile(&values, 50.0), 5.5);
+        assert_eq!(percentile(&values, 100.0), 10.0);
+    }
+
+    #[test]
+    fn test_percentile_empty() {
+        let values: Vec<f64> = vec![];
+        assert_eq!(percentile(&values, 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_percentile_single() {
+        let values = vec![42.0];
+        assert_eq!(percentile(&values, 50.0), 42.0);
+        assert_eq!(percentile(&values, 95.0), 42.0);
+    }
+
+    #[test]
+    fn test_calculate_stats_trend() {
+        let values = vec![100.0, 200.0, 300.0, 400.0, 500.0];
+        let stats = calculate_stats(&values, MetricType::Trend, &default_trend_stats(), None);
+
+        assert_eq!(stats.get("avg"), Some(&300.0));
+        assert_eq!(stats.get("min"), Some(&100.0));
+        assert_eq!(stats.get("max"), Some(&500.0));
+        assert_eq!(stats.get("med"), Some(&300.0));
+    }
+
+    #[test]
+    fn test_calculate_stats_counter() {
+        let values = vec![1.0, 1.0, 1.0, 1.0, 1.0];
+        let stats = calculate_stats(
+            &values,
+            MetricType::Counter,
+            &default_trend_stats(),
+            Some(2500.0),
+        );
+
+        assert_eq!(stats.get("count"), Some(&5.0));
+        assert_eq!(stats.get("rate"), Some(&2.0));
+    }
+
+    #[test]
+    fn test_calculate_stats_counter_sums_values() {
+        let values = vec![512.0, 1024.0, 2048.0];
+        let stats = calculate_stats(&values, MetricType::Counter, &[], Some(1000.0));
+
+        assert_eq!(stats.get("count"), Some(&3584.0));
+        assert_eq!(stats.get("rate"), Some(&3584.0));
+        assert!(!stats.contains_key("avg"));
+    }
+
+    #[test]
+    fn test_calculate_stats_gauge_last_value() {
+        let values = vec![1.0, 10.0, 4.0];
+        let stats = calculate_stats(&values, MetricType::Gauge, &[], None);
+
+        assert_eq!(stats.get("value"), Some(&4.0));
+        assert_eq!(stats.get("min"), Some(&1.0));
+        assert_eq!(stats.get("max"), Some(&10.0));
+    }
+
+    #[test]
+    fn test_combine_inputs() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let load_summary = || -> K6Summary {
+            serde_json::from_str(include_str!("../tests/fixtures/k6-run-summary.json")).unwrap()
+        };
+        let points = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+
+        let combined = combine_inputs(load_summary(), points, 1.0);
+        assert!(combined.warnings.is_empty(), "{:?}", combined.warnings);
+        assert_eq!(
+            combined.metrics["http_req_duration"].values,
+            load_summary().metrics["http_req_duration"].values
+        );
+        assert_eq!(combined.state.unwrap().test_run_duration_ms, 9541.746);
+        assert!(!combined.timeline.is_empty());
+        assert!(!combined.endpoints.is_empty());
+        assert!(combined.samples.contains_key("http_req_duration"));
+
+        // Drop a tenth of the requests from the points
+        let partial: String = jsonl
+            .lines()
+            .filter(|line| !line.contains(r#""metric":"http_reqs","type":"Point""#))
+            .chain(
+                jsonl
+                    .lines()
+                    .filter(|line| line.contains(r#""metric":"http_reqs","type":"Point""#))
+                    .skip(4),
+            )
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let points = parse_jsonl(partial.as_bytes(), &ParseOptions::default()).unwrap();
+        let combined = combine_inputs(load_summary(), points, 1.0);
+        assert_eq!(
+            combined.warnings,
+            vec!["http_reqs totals differ: 44 in the summary, 40 in the points (9.1% > 1% tolerance)"]
+        );
+
+        let points = parse_jsonl(partial.as_bytes(), &ParseOptions::default()).unwrap();
+        assert!(combine_inputs(load_summary(), points, 10.0)
+            .warnings
+            .is_empty());
+    }
+
+    #[test]
+    fn test_time_bound_from_str() {
+        assert_eq!("30s".parse(), Ok(TimeBound::Offset(30_000.0)));
+        assert_eq!("1m30s".parse(), Ok(TimeBound::Offset(90_000.0)));
+        assert_eq!("-2m".parse(), Ok(TimeBound::Offset(-120_000.0)));
+        assert_eq!(
+            "2024-06-01T10:00:05Z".parse(),
+            Ok(TimeBound::Absolute(1_717_236_005_000.0))
+        );
+        assert!("soon".parse::<TimeBound>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_window() {
+        // Ramp 1 -> 10 VUs over 4s, hold for 4s, ramp down
+        let vus: Vec<(f64, f64)> = [1.0, 4.0, 7.0, 10.0, 10.0, 9.5, 10.0, 10.0, 10.0, 5.0, 1.0]
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| (idx as f64 * 1000.0, *v))
+            .collect();
+        let resolve = |options: WindowOptions| resolve_window(&options, 0.0, 10_000.0, &vus);
+
+        assert_eq!(resolve(WindowOptions::default()).unwrap(), None);
+
+        let steady = resolve(WindowOptions {
+            steady_state: true,
+            ..WindowOptions::default()
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!((steady.start_ms, steady.end_ms), (3000.0, 8000.0));
+        assert_eq!(steady.steady_vus, Some(10.0));
+
+        let window = resolve(WindowOptions {
+            from: Some(TimeBound::Offset(2000.0)),
+            to: Some(TimeBound::Offset(-3000.0)),
+            steady_state: false,
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!((window.start_ms, window.end_ms), (2000.0, 7000.0));
+        assert_eq!(window.run_ms, 10_000.0);
+
+        let empty = resolve(WindowOptions {
+            from: Some(TimeBound::Offset(20_000.0)),
+            ..WindowOptions::default()
+        });
+        assert!(matches!(empty, Err(Error::Window(_))));
+        assert!(matches!(
+            resolve_window(
+                &WindowOptions {
+                    steady_state: true,
+                    ..WindowOptions::default()
+                },
+                0.0,
+                1000.0,
+                &[]
+            ),
+            Err(Error::Window(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_offset() {
+        assert_eq!(format_offset(0.0), "0:00");
+        assert_eq!(format_offset(90_400.0), "1:30");
+        assert_eq!(format_offset(3_723_000.0), "1:02:03");
+    }
+
+    #[test]
+    fn test_tag_filter_from_str() {
+        let filter: TagFilter = "status!=5*".parse().unwrap();
+        assert_eq!(
+            filter,
+            TagFilter {
+                key: "status".to_string(),
+                pattern: "5*".to_string(),
+                negate: true,
+            }
+        );
+        assert_eq!(filter.to_string(), "status!=5*");
+        assert!(!"scenario=checkout".parse::<TagFilter>().unwrap().negate);
+        assert!("scenario".parse::<TagFilter>().is_err());
+        assert!("=value".parse::<TagFilter>().is_err());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("checkout", "checkout"));
+        assert!(!glob_match("checkout", "checkouts"));
+        assert!(glob_match("eu-*", "eu-west-1"));
+        assert!(glob_match("*api*v?", "https://api.test/v2"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+    }
+
+    #[test]
+    fn test_matches_tag_filters() {
+        let filters: Vec<TagFilter> = ["region=eu-*", "region=us-east", "status!=5*"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let tags = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+        assert!(matches_tag_filters(
+            &filters,
+            &tags(&[("region", "eu-west"), ("status", "200")])
+        ));
+        assert!(matches_tag_filters(
+            &filters,
+            &tags(&[("region", "us-east")])
+        ));
+        assert!(!matches_tag_filters(
+            &filters,
+            &tags(&[("region", "ap-south")])
+        ));
+        assert!(!matches_tag_filters(
+            &filters,
+            &tags(&[("region", "eu-west"), ("status", "503")])
+        ));
+        assert!(!matches_tag_filters(&filters, &tags(&[("status", "200")])));
+        // Global gauges such as vus carry no tags
+        assert!(matches_tag_filters(&filters, &tags(&[])));
+    }
+
+    #[test]
+    fn test_calculate_stats_rate() {
+        let values = vec![1.0, 1.0, 1.0, 0.0, 0.0]; // 3 passes, 2 fails
+        let stats = calculate_stats(&values, MetricType::Rate, &default_trend_stats(), None);
+
+        assert_eq!(stats.get("passes"), Some(&3.0));
+        assert_eq!(stats.get("fails"), Some(&2.0));
+        assert_eq!(stats.get("rate"), Some(&0.6)); // 3/5 = 0.6
+    }
+
+    #[test]
+    fn test_log_histogram_buckets() {
+        let sorted = vec![12.0, 15.0, 18.0, 45.0, 120.0];
+        let buckets = log_histogram(&sorted);
+
+        let bounds: Vec<(f64, f64, usize)> = buckets
+            .iter()
+            .map(|b| (b.lower, b.upper, b.count))
+            .collect();
+        assert_eq!(
+            bounds,
+            vec![
+                (10.0, 20.0, 3),
+                (20.0, 50.0, 1),
+                (50.0, 100.0, 0),
+                (100.0, 200.0, 1)
+            ]
+        );
+
+        let sorted = vec![f64::NEG_INFINITY, 12.0, f64::INFINITY, f64::NAN];
+        let buckets = log_histogram(&sorted);
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].count, 1);
+        assert!(log_histogram(&[f64::NAN]).is_empty());
+    }
+
+    #[test]
+    fn test_log_floor_sub_millisecond() {
+        assert_eq!(log_floor(0.3), 0.2);
+        assert_eq!(log_floor(0.05), 0.05);
+        assert_eq!(log_next(0.5), 1.0);
+    }
+
+    #[test]
+    fn test_value_at_percentile() {
+        let values: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        assert_eq!(value_at_percentile(&values, 0.0), 1.0);
+        assert_eq!(value_at_percentile(&values, 0.5), 50.0);
+        assert_eq!(value_at_percentile(&values, 0.999), 100.0);
+    }
+
+    #[test]
+    fn test_format_hgrm() {
+        let values: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        let hgrm = format_hgrm(&values);
+
+        assert!(hgrm.starts_with("       Value     Percentile TotalCount 1/(1-Percentile)\n\n"));
+        assert!(hgrm.contains("       1.000 0.000000000000          1           1.00\n"));
+        assert!(hgrm.contains("      10.000 1.000000000000         10\n"));
+        assert!(hgrm.contains("#[Max     =       10.000, Total count    =           10]"));
+    }
+
+    #[test]
+    fn test_trend_stat_from_str() {
+        assert_eq!("avg".parse::<TrendStat>(), Ok(TrendStat::Avg));
+        assert_eq!("count".parse::<TrendStat>(), Ok(TrendStat::Count));
+        assert_eq!(
+            "p(99.9)".parse::<TrendStat>(),
+            Ok(TrendStat::Percentile(99.9))
+        );
+        assert!("p(101)".parse::<TrendStat>().is_err());
+        assert!("mean".parse::<TrendStat>().is_err());
+        assert_eq!(TrendStat::Percentile(99.9).key(), "p(99.9)");
+        assert_eq!(TrendStat::Percentile(90.0).key(), "p(90)");
+    }
+
+    #[test]
+    fn test_calculate_stats_custom_trend_stats() {
+        let values: Vec<f64> = (1..=100).map(|v| v as f64).collect();
+        let trend_stats: Vec<TrendStat> = ["avg", "p(75)", "p(99.9)", "count"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let stats = calculate_stats(&values, MetricType::Trend, &trend_stats, None);
+
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats.get("avg"), Some(&50.5));
+        assert_eq!(stats.get("p(75)"), Some(&75.25));
+        assert_eq!(stats.get("count"), Some(&100.0));
+        assert!(stats.contains_key("p(99.9)"));
+        assert!(!stats.contains_key("med"));
+    }
+
+    #[test]
+    fn test_evaluate_threshold() {
+        let values: HashMap<String, f64> = [("p(95)", 450.0), ("rate", 0.02), ("count", 100.0)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        let stat = |name: &str| values.get(name).copied();
+
+        assert_eq!(evaluate_threshold("p(95)<500", stat), Some(true));
+        assert_eq!(evaluate_threshold("p(95) < 400", stat), Some(false));
+        assert_eq!(evaluate_threshold("rate<=0.02", stat), Some(true));
+        assert_eq!(evaluate_threshold("count>=100", stat), Some(true));
+        assert_eq!(evaluate_threshold("count===100", stat), Some(true));
+        assert_eq!(evaluate_threshold("count!=100", stat), Some(false));
+        assert_eq!(evaluate_threshold("p(99)<500", stat), None);
+        assert_eq!(evaluate_threshold("p(95)", stat), None);
+    }
+
+    #[test]
+    fn test_apply_thresholds_to_summary() {
+        let json = include_str!("../tests/fixtures/k6-run-summary.json");
+        let mut summary: K6Summary = serde_json::from_str(json).unwrap();
+        let before = summary.metrics["http_req_duration"].thresholds.len();
+
+        let thresholds = BTreeMap::from([
+            (
+                "http_req_duration".to_string(),
+                vec!["p(95)<100000".to_string(), "p(99.99)<1".to_string()],
+            ),
+            ("missing_metric".to_string(), vec!["count>0".to_string()]),
+        ]);
+        apply_thresholds(&mut summary, &thresholds);
+
+        let added = &summary.metrics["http_req_duration"].thresholds;
+        assert_eq!(added.len(), before + 2);
+        assert!(added["p(95)<100000"].ok);
+        assert!(added["p(99.99)<1"].unknown);
+        assert!(!summary.metrics.contains_key("missing_metric"));
+    }
+
+    #[test]
+    fn test_run_overview() {
+        let json = include_str!("../tests/fixtures/k6-summary-export.json");
+        let mut summary = crate::parse::parse_summary_export(json).unwrap();
+        let overview = RunOverview::of(&summary);
+        assert!(overview.duration_ms.is_some());
+        assert!(overview.rps.unwrap() > 0.0);
+        assert!(overview.p95.unwrap() > 0.0);
+        assert_eq!(overview.verdict, None);
+        let thresholds =
+            BTreeMap::from([("http_req_failed".to_string(), vec!["rate<0.01".to_string()])]);
+        apply_thresholds(&mut summary, &thresholds);
+        assert_eq!(RunOverview::of(&summary).verdict, Some(Verdict::Failed));
+
+        let json = include_str!("../tests/fixtures/k6-run-summary.json");
+        let mut summary: K6Summary = serde_json::from_str(json).unwrap();
+        assert_eq!(RunOverview::of(&summary).verdict, None);
+        let thresholds = BTreeMap::from([(
+            "http_req_duration".to_string(),
+            vec!["p(95)<100000".to_string()],
+        )]);
+        apply_thresholds(&mut summary, &thresholds);
+        assert_eq!(RunOverview::of(&summary).verdict, Some(Verdict::Passed));
+    }
+
+    #[test]
+    fn test_histogram_percentiles_within_one_percent() {
+        let mut histogram = Histogram::default();
+        let mut values: Vec<f64> = (1..=1000).map(|i| i as f64 * 0.7).collect();
+        values.extend([0.0, -5.0]);
+        for value in &values {
+            histogram.add(*value);
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for p in [0.0, 50.0, 90.0, 95.0, 99.0, 100.0] {
+            let exact = percentile(&values, p);
+            let approx = histogram.percentile(p).unwrap();
+            assert!(
+                (approx - exact).abs() <= exact.abs() * 0.01 + 0.7,
+                "p({})",
+                p
+            );
+        }
+        assert_eq!(histogram.percentile(0.0), Some(-5.0));
+        assert_eq!(histogram.percentile(100.0), Some(700.0));
+        assert_eq!(Histogram::default().percentile(50.0), None);
+    }
+
+    #[test]
+    fn test_rolling_stats() {
+        let thresholds = BTreeMap::from([
+            ("http_req_failed".to_string(), vec!["rate<0.1".to_string()]),
+            ("checks".to_string(), vec!["rate>0.9".to_string()]),
+        ]);
+        let mut stats = RollingStats::new(10_000.0, thresholds);
+        stats.declare(
+            "http_req_duration",
+            MetricType::Trend,
+            &["p(95)<500".to_string(), "avg<100".to_string()],
+        );
+        for second in 0..30 {
+            let time = Some(second as f64 * 1000.0);
+            stats.add_point("vus", MetricType::Gauge, time, second as f64);
+            stats.add_point("http_reqs", MetricType::Counter, time, 2.0);
+            // Slow and failing only during the first 10 seconds
+            let (duration, failed) = if second < 10 {
+                (900.0, 1.0)
+            } else {
+                (50.0, 0.0)
+            };
+            stats.add_point("http_req_duration", MetricType::Trend, time, duration);
+            stats.add_point("http_req_failed", MetricType::Rate, time, failed);
+        }
+
+        let now = stats.snapshot();
+        assert_eq!(now.elapsed_ms, 29_000.0);
+        assert_eq!(now.vus, Some(29.0));
+        assert_eq!(now.requests, 60.0);
+        assert_eq!(now.rps, Some(2.2));
+        assert_eq!(now.p95, Some(50.0));
+        assert_eq!(now.error_rate, Some(0.0));
+
+        // Thresholds cover the whole run so far
+        let outcomes: Vec<(&str, &str, bool, bool)> = stats
+            .thresholds()
+            .into_iter()
+            .map(|(metric, expr, t)| (metric, expr, t.ok, t.unknown))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("checks", "rate>0.9", false, true),
+                ("http_req_duration", "p(95)<500", false, false),
+                ("http_req_duration", "avg<100", false, false),
+                ("http_req_failed", "rate<0.1", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rolling_stats_window_moves_without_requests() {
+        let mut stats = RollingStats::new(10_000.0, BTreeMap::new());
+        for second in 0..5 {
+            let time = Some(second as f64 * 1000.0);
+            stats.add_point("http_reqs", MetricType::Counter, time, 1.0);
+            stats.add_point("http_req_duration", MetricType::Trend, time, 100.0);
+            stats.add_point("http_req_failed", MetricType::Rate, time, 1.0);
+        }
+        // Requests stop, but VUs keep reporting while the run ramps down
+        for second in 5..30 {
+            let time = Some(second as f64 * 1000.0);
+            stats.add_point("vus", MetricType::Gauge, time, 1.0);
+        }
+
+        let now = stats.snapshot();
+        assert_eq!(now.requests, 5.0);
+        assert_eq!(now.rps, Some(0.0));
+        assert_eq!(now.p95, None);
+        assert_eq!(now.error_rate, None);
+    }
+}
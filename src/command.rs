@@ -0,0 +1,101 @@
+//! The commands of the `k6r` binary, each with its flags as a clap
+//! [`Args`](clap::Args) struct: a single [`report`], [`watch`] mode,
+//! [`batch`] rendering of a directory, following a [`live`] run, and a
+//! report server that [`serve`]s a directory.
+
+use crate::config::CONFIG_FILE_NAME;
+use crate::Error;
+use std::path::{Path, PathBuf};
+
+pub mod batch;
+pub mod live;
+pub mod report;
+pub mod serve;
+pub mod watch;
+
+// =============================================================================
+// Result Directories
+// =============================================================================
+
+/// Extensions of the reports k6r writes, never taken for results
+pub(crate) const REPORT_EXTENSIONS: [&str; 3] = ["md", "html", "txt"];
+
+/// The files in `dir` that may hold results, by name
+pub(crate) fn result_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries = std::fs::read_dir(dir).map_err(|source| Error::Read {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| is_result_file(path))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Whether a file in a results directory may hold results: not a report,
+/// the config, or a hidden or backup file
+pub(crate) fn is_result_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(is_result_name)
+        && path.is_file()
+}
+
+/// Whether a file name may be one of results, see [`is_result_file`]
+pub(crate) fn is_result_name(name: &str) -> bool {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.ends_with('~')
+        && !name.contains(['/', '\\'])
+        && name != CONFIG_FILE_NAME
+        && !REPORT_EXTENSIONS.contains(&extension)
+}
+
+/// Whether `path` exists and was modified after `than`
+pub(crate) fn is_newer(path: &Path, than: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(path), modified(than)) {
+        (Some(time), Some(than)) => time >= than,
+        _ => false,
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_result_file() {
+        let dir = std::env::temp_dir().join(format!("k6r-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "run.json",
+            "run.csv.gz",
+            "run.md",
+            ".run.json.swp",
+            CONFIG_FILE_NAME,
+        ] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert!(is_result_file(&dir.join("run.json")));
+        assert!(is_result_file(&dir.join("run.csv.gz")));
+        assert!(!is_result_file(&dir.join("run.md")));
+        assert!(!is_result_file(&dir.join(".run.json.swp")));
+        assert!(!is_result_file(&dir.join(CONFIG_FILE_NAME)));
+        assert!(!is_result_file(&dir.join("missing.json")));
+        assert!(is_newer(&dir.join("run.md"), &dir.join("run.json")));
+        assert!(!is_newer(&dir.join("missing.md"), &dir.join("run.json")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+//! Batch mode: the reports of every result file in a directory, rendered in
+//! parallel, and index pages linking them.
+
+use super::result_files;
+use crate::analyze::RunOverview;
+use crate::config::{report_file, write_report, ReportArgs, ReportSettings};
+use crate::parse::FileFormat;
+use crate::render::{
+    build_index_document, url_path, IndexEntry, OutputFormat, Renderer, Terminal,
+    DEFAULT_INDEX_TITLE,
+};
+use crate::Error;
+use clap::Args;
+use rayon::prelude::*;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// Flags of `k6r batch`
+#[derive(Args, Clone)]
+#[non_exhaustive]
+pub struct BatchArgs {
+    /// Directory of k6 result files
+    #[arg(value_name = "DIR")]
+    pub dir: PathBuf,
+
+    /// Directory to write the reports and the index page into
+    #[arg(value_name = "OUTDIR")]
+    pub outdir: PathBuf,
+
+    /// Formats of the index page, e.g. markdown,html [default: html for HTML reports,
+    /// else markdown]
+    #[arg(long, value_enum, value_name = "FORMATS", value_delimiter = ',')]
+    pub index: Vec<OutputFormat>,
+
+    /// Read every input as this format instead of detecting it
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub input_format: Option<FileFormat>,
+
+    /// How to build the reports
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// Renders the reports of every result file in a directory in parallel, then
+/// the index pages linking them
+pub fn run(args: BatchArgs) -> Result<(), Error> {
+    let settings = ReportSettings::load(args.report)?;
+    let inputs = result_files(&args.dir)?;
+    if inputs.is_empty() {
+        return Err(Error::NoInputs(args.dir));
+    }
+    std::fs::create_dir_all(&args.outdir).map_err(|source| Error::Write {
+        path: args.outdir.clone(),
+        source,
+    })?;
+
+    let format = settings.format.unwrap_or_default();
+    let mut index_formats = args.index;
+    if index_formats.is_empty() {
+        index_formats.push(default_index_format(format));
+    }
+    let indexes = index_files(index_formats);
+    let index_names: Vec<String> = indexes.iter().map(|(_, name)| name.clone()).collect();
+    let names = report_names(&inputs, format, &index_names);
+
+    let results: Vec<Result<RunOverview, Error>> = inputs
+        .par_iter()
+        .zip(&names)
+        .map(|(input, name)| {
+            let output_path = args.outdir.join(name);
+            let summary = report_file(&settings, input, args.input_format, format, &output_path)?;
+            Ok(RunOverview::of(&summary))
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    for ((input, name), result) in inputs.iter().zip(names).zip(results) {
+        let input_name = input.file_name().unwrap_or_default().to_string_lossy();
+        match result {
+            Ok(overview) => entries.push(IndexEntry::new(input_name, url_path(&name), overview)),
+            Err(e) => {
+                eprintln!("Skipped {}: {}", input.display(), e);
+                skipped.push((input_name.to_string(), e.to_string()));
+            }
+        }
+    }
+
+    let document = build_index_document(
+        DEFAULT_INDEX_TITLE,
+        &entries,
+        &skipped,
+        &settings.options.units,
+    );
+    for (format, name) in indexes {
+        let index = match format {
+            OutputFormat::Term => Terminal { color: false }.render(&document),
+            format => format.renderer().render(&document),
+        };
+        write_report(&index, &args.outdir.join(name))?;
+    }
+    eprintln!(
+        "{} of {} reports generated in {}",
+        entries.len(),
+        inputs.len(),
+        args.outdir.display()
+    );
+    Ok(())
+}
+
+/// The index page written for reports in `format` when none is asked for
+pub(crate) fn default_index_format(format: OutputFormat) -> OutputFormat {
+    match format {
+        OutputFormat::Html => OutputFormat::Html,
+        _ => OutputFormat::Markdown,
+    }
+}
+
+/// The index pages to write and their file names; formats sharing a file
+/// name, such as text and term, are written once
+pub(crate) fn index_files(formats: Vec<OutputFormat>) -> Vec<(OutputFormat, String)> {
+    let mut files: Vec<(OutputFormat, String)> = Vec::new();
+    for format in formats {
+        let name = format!("index.{}", format.extension());
+        if !files.iter().any(|(_, other)| *other == name) {
+            files.push((format, name));
+        }
+    }
+    files
+}
+
+/// Report file names for the inputs: the input's name with the format's
+/// extension, or with the extension added where that would be ambiguous,
+/// and numbered if even that is taken
+pub(crate) fn report_names(
+    inputs: &[PathBuf],
+    format: OutputFormat,
+    reserved: &[String],
+) -> Vec<String> {
+    let extension = format.extension();
+    let short = |input: &PathBuf| {
+        input
+            .with_extension(extension)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let shorts: Vec<String> = inputs.iter().map(short).collect();
+    let is_unique = |name: &String| {
+        shorts.iter().filter(|other| *other == name).count() == 1 && !reserved.contains(name)
+    };
+
+    // Every name in use so far; the kept short names are claimed up front
+    let mut taken: BTreeSet<String> = reserved.iter().cloned().collect();
+    taken.extend(shorts.iter().filter(|name| is_unique(name)).cloned());
+    inputs
+        .iter()
+        .zip(&shorts)
+        .map(|(input, short)| {
+            if is_unique(short) {
+                return short.clone();
+            }
+            let file_name = input.file_name().unwrap_or_default().to_string_lossy();
+            let mut name = format!("{}.{}", file_name, extension);
+            let mut number = 1;
+            while !taken.insert(name.clone()) {
+                number += 1;
+                name = format!("{}-{}.{}", file_name, number, extension);
+            }
+            name
+        })
+        .collect()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_batch_report_names() {
+        let inputs: Vec<PathBuf> = ["a.json", "a.csv", "b.jsonl", "index.json"]
+            .iter()
+            .map(|name| Path::new("results").join(name))
+            .collect();
+        let reserved = vec!["index.html".to_string()];
+        assert_eq!(
+            report_names(&inputs, OutputFormat::Html, &reserved),
+            ["a.json.html", "a.csv.html", "b.html", "index.json.html"]
+        );
+
+        // `a.json.gz` keeps `a.json.md`, which `a.json` would also get
+        let inputs: Vec<PathBuf> = ["a.json", "a.csv", "a.json.gz"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(
+            report_names(&inputs, OutputFormat::Markdown, &[]),
+            ["a.json-2.md", "a.csv.md", "a.json.md"]
+        );
+
+        let formats = vec![
+            OutputFormat::Text,
+            OutputFormat::Term,
+            OutputFormat::Markdown,
+        ];
+        assert_eq!(
+            index_files(formats),
+            [
+                (OutputFormat::Text, "index.txt".to_string()),
+                (OutputFormat::Markdown, "index.md".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Live mode: a dashboard of a run in progress, followed from k6's JSONL
+//! output, and its report once the run ends.
+
+use crate::analyze::LiveSnapshot;
+use crate::config::{write_report, ReportArgs, ReportSettings};
+use crate::parse::{parse_duration_ms, JsonlStream, ROLLING_WINDOW, STDIO_PATH};
+use crate::render::{build_live_document, Renderer, Terminal};
+use crate::Error;
+use clap::Args;
+use signal_hook::consts::SIGINT;
+use std::collections::VecDeque;
+use std::io::{BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Flags of `k6r live`
+#[derive(Args, Clone)]
+#[non_exhaustive]
+pub struct LiveArgs {
+    /// k6 --out json results to follow, or '-' for stdin (k6 run --out json=/dev/stdout)
+    #[arg(value_name = "JSON_FILE")]
+    pub input: PathBuf,
+
+    /// Output report file written when the run ends, '-' for stdout (defaults as for a
+    /// single report)
+    #[arg(value_name = "REPORT_FILE")]
+    pub output: Option<PathBuf>,
+
+    /// How often the dashboard refreshes
+    #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = parse_interval)]
+    pub refresh: Duration,
+
+    /// Span at the end of the data that the current request rate, p95 and error rate cover
+    #[arg(long, value_name = "DURATION", default_value = "10s", value_parser = parse_interval)]
+    pub window: Duration,
+
+    /// Stop following a file once it has not grown for this long
+    #[arg(long, value_name = "DURATION", default_value = "30s", value_parser = parse_interval)]
+    pub idle: Duration,
+
+    /// How to build the reports
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// A positive duration such as `500ms`, `2s` or `1m`
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    match parse_duration_ms(s) {
+        Some(ms) if ms > 0.0 => Ok(Duration::from_secs_f64(ms / 1000.0)),
+        _ => Err(format!("expected a duration like 1s or 500ms, got '{}'", s)),
+    }
+}
+
+/// How often a followed file is checked for new lines once at its end
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Dashboard refreshes kept for its sparklines
+const LIVE_HISTORY_LEN: usize = 60;
+
+/// Follows JSONL points until the input ends or Ctrl-C, then writes the report
+pub fn run(args: LiveArgs) -> Result<(), Error> {
+    let settings = ReportSettings::load(args.report)?;
+    if settings.parse_options.window.is_set() {
+        return Err(Error::Window(ROLLING_WINDOW.to_string()));
+    }
+    let (format, output_path) = settings.output(args.output.as_deref(), &args.input);
+    let lines = follow_lines(&args.input, args.idle)?;
+
+    // The first Ctrl-C ends the run and writes the report, a second one exits
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let registered =
+        signal_hook::flag::register_conditional_shutdown(SIGINT, 1, Arc::clone(&interrupted))
+            .and_then(|_| signal_hook::flag::register(SIGINT, Arc::clone(&interrupted)));
+    if let Err(e) = registered {
+        eprintln!("Warning: Ctrl-C will not write the report: {}", e);
+    }
+    eprintln!("Following {} (Ctrl-C to stop)", args.input.display());
+
+    let window_ms = args.window.as_secs_f64() * 1000.0;
+    let mut stream = JsonlStream::new(&settings.parse_options).with_rolling_stats(window_ms);
+    let dashboard = std::io::stderr().is_terminal().then(Terminal::for_stderr);
+    let mut history: VecDeque<LiveSnapshot> = VecDeque::new();
+    let mut draw = |stream: &JsonlStream| {
+        let (Some(terminal), Some(stats)) = (&dashboard, stream.rolling_stats()) else {
+            return;
+        };
+        if history.len() == LIVE_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(stats.snapshot());
+        let document = build_live_document(
+            stats,
+            history.make_contiguous(),
+            window_ms,
+            &settings.options,
+        );
+        // Home the cursor and clear the screen before each frame
+        eprint!("\x1b[H\x1b[2J{}", terminal.render(&document));
+    };
+
+    let mut next_refresh = Instant::now();
+    while !interrupted.load(Ordering::Relaxed) {
+        let timeout = next_refresh.saturating_duration_since(Instant::now());
+        match lines.recv_timeout(timeout) {
+            Ok(line) => stream.push_line(&line?)?,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if Instant::now() >= next_refresh {
+            draw(&stream);
+            next_refresh = Instant::now() + args.refresh;
+        }
+    }
+    draw(&stream);
+
+    let mut summary = stream.finish()?;
+    settings.complete(&mut summary);
+    let report = settings.render(format, &summary, &output_path)?;
+    write_report(&report, &output_path)
+}
+
+/// Reads lines on a thread as they are written: stdin until it ends, a file
+/// until it has not grown for `idle`. A line still being written is held back
+/// until its line ending arrives.
+fn follow_lines(path: &Path, idle: Duration) -> Result<Receiver<Result<Vec<u8>, Error>>, Error> {
+    let (sender, receiver) = mpsc::channel();
+    if path == Path::new(STDIO_PATH) {
+        std::thread::spawn(move || send_lines(std::io::stdin().lock(), None, sender));
+    } else {
+        let file = std::fs::File::open(path).map_err(|source| Error::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let reader = std::io::BufReader::new(file);
+        std::thread::spawn(move || send_lines(reader, Some(idle), sender));
+    }
+    Ok(receiver)
+}
+
+fn send_lines(
+    mut reader: impl BufRead,
+    idle: Option<Duration>,
+    sender: Sender<Result<Vec<u8>, Error>>,
+) {
+    let mut buf = Vec::new();
+    let mut last_growth = Instant::now();
+    loop {
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => match idle {
+                Some(idle) if last_growth.elapsed() < idle => {
+                    std::thread::sleep(FOLLOW_POLL_INTERVAL)
+                }
+                _ => break,
+            },
+            Ok(_) => {
+                last_growth = Instant::now();
+                if buf.ends_with(b"\n") && sender.send(Ok(std::mem::take(&mut buf))).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let _ = sender.send(Err(Error::Input(e)));
+                return;
+            }
+        }
+    }
+    if !buf.is_empty() {
+        let _ = sender.send(Ok(buf));
+    }
+}
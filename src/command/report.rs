@@ -0,0 +1,191 @@
+//! A single report of a result file, or of a summary and the points of the
+//! same run.
+
+use crate::analyze::{combine_inputs, format_hgrm, latency_metric_names};
+use crate::config::{write_report, ReportSettings};
+use crate::parse::{open_detected, Confidence, FileFormat};
+use crate::{Error, K6Summary};
+use clap::Args;
+use std::path::PathBuf;
+
+/// Allowed difference in percent between summary and point totals by default
+pub const DEFAULT_TOLERANCE: f64 = 1.0;
+
+/// The inputs of a report and where it goes
+#[derive(Args, Clone)]
+#[non_exhaustive]
+pub struct InputArgs {
+    /// Input k6 results: handleSummary or --summary-export JSON, or --out json/csv points ('-' for stdin)
+    #[arg(
+        value_name = "JSON_FILE",
+        required_unless_present_any = ["summary", "points", "print_template"]
+    )]
+    pub input: Option<PathBuf>,
+
+    /// Output report file, '-' for stdout (defaults to the input, summary or points filename
+    /// with the format's extension, or stdout for stdin input and the term format)
+    #[arg(value_name = "REPORT_FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Read the input as this format instead of detecting it
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub input_format: Option<FileFormat>,
+
+    /// Summary of the run (handleSummary or --summary-export JSON); its thresholds,
+    /// checks and aggregate stats take precedence over the points
+    #[arg(long, value_name = "JSON_FILE")]
+    pub summary: Option<PathBuf>,
+
+    /// Points of the same run (--out json or --out csv) for the time-based sections
+    #[arg(long, value_name = "FILE")]
+    pub points: Option<PathBuf>,
+
+    /// Allowed difference in percent between summary and point totals before warning
+    #[arg(long, value_name = "PERCENT", default_value_t = DEFAULT_TOLERANCE)]
+    pub tolerance: f64,
+
+    /// Export the percentile spectrum of each latency trend as <DIR>/<metric>.hgrm (points only)
+    #[arg(long, value_name = "DIR")]
+    pub hgrm: Option<PathBuf>,
+}
+
+impl Default for InputArgs {
+    fn default() -> Self {
+        InputArgs {
+            input: None,
+            output: None,
+            input_format: None,
+            summary: None,
+            points: None,
+            tolerance: DEFAULT_TOLERANCE,
+            hgrm: None,
+        }
+    }
+}
+
+impl InputArgs {
+    /// The positional input and output, which is the input when both --summary
+    /// and --points are given
+    pub fn positional_paths(&self) -> (Option<&PathBuf>, Option<&PathBuf>) {
+        match (&self.input, &self.output) {
+            (Some(input), None) if self.summary.is_some() && self.points.is_some() => {
+                (None, Some(input))
+            }
+            (input, output) => (input.as_ref(), output.as_ref()),
+        }
+    }
+}
+
+/// Writes the report of the inputs
+pub fn run(args: &InputArgs, settings: &ReportSettings) -> Result<(), Error> {
+    let (input, output) = args.positional_paths();
+    let first_input = [input, args.summary.as_ref(), args.points.as_ref()]
+        .into_iter()
+        .flatten()
+        .next()
+        .ok_or_else(|| Error::Combine("no input given".to_string()))?;
+    let (format, output_path) = settings.output(output.map(PathBuf::as_path), first_input);
+    let parse_options = &settings.parse_options;
+
+    // The positional input takes whichever role its format implies
+    let mut summary_input: Option<(&PathBuf, K6Summary)> = None;
+    let mut points_input: Option<(&PathBuf, K6Summary)> = None;
+    let inputs = [
+        (input, None),
+        (args.summary.as_ref(), Some(true)),
+        (args.points.as_ref(), Some(false)),
+    ];
+    for (path, expect_summary) in inputs {
+        let Some(path) = path else { continue };
+        // --input-format applies to inputs whose role it fits
+        let forced = args
+            .input_format
+            .filter(|format| expect_summary.is_none_or(|s| s == format.is_summary()));
+        let input = open_detected(path, forced)?;
+        let detection = &input.detection;
+        match detection.confidence {
+            Some(confidence) => eprintln!(
+                "Detected format: {} ({} confidence)",
+                detection.format.name(),
+                confidence
+            ),
+            None => eprintln!("Input format: {}", detection.format.name()),
+        }
+        if detection.confidence < Some(Confidence::High) {
+            for (format, confidence) in &detection.alternatives {
+                eprintln!(
+                    "  could also be: {} ({} confidence)",
+                    format.name(),
+                    confidence
+                );
+            }
+        }
+        let is_summary = detection.format.is_summary();
+        let kind = if is_summary {
+            "summaries"
+        } else {
+            "point files"
+        };
+        if expect_summary.is_some_and(|expected| expected != is_summary) {
+            return Err(Error::Combine(format!(
+                "'{}' is not a {}",
+                path.display(),
+                if is_summary { "point file" } else { "summary" }
+            )));
+        }
+        let slot = if is_summary {
+            &mut summary_input
+        } else {
+            &mut points_input
+        };
+        if let Some((other, _)) = slot {
+            return Err(Error::Combine(format!(
+                "'{}' and '{}' are both {}",
+                other.display(),
+                path.display(),
+                kind
+            )));
+        }
+        *slot = Some((path, input.parse(parse_options)?));
+    }
+
+    if points_input.is_none() {
+        if !parse_options.tag_filters.is_empty() {
+            return Err(Error::NeedsPoints("Tag filters"));
+        }
+        if parse_options.window.is_set() {
+            return Err(Error::NeedsPoints("Time windows"));
+        }
+    }
+
+    let mut summary = match (summary_input, points_input) {
+        (Some((_, summary)), Some((_, points))) => combine_inputs(summary, points, args.tolerance),
+        (Some((_, summary)), None) | (None, Some((_, summary))) => summary,
+        (None, None) => unreachable!("an input was given"),
+    };
+    settings.complete(&mut summary);
+
+    if let Some(dir) = &args.hgrm {
+        if summary.samples.is_empty() {
+            eprintln!("No raw latency samples available; .hgrm export requires JSONL or CSV input");
+        } else {
+            std::fs::create_dir_all(dir).map_err(|source| Error::Write {
+                path: dir.clone(),
+                source,
+            })?;
+            for name in latency_metric_names(&summary) {
+                let path = dir.join(format!("{}.hgrm", name));
+                std::fs::write(&path, format_hgrm(&summary.samples[name])).map_err(|source| {
+                    Error::Write {
+                        path: path.clone(),
+                        source,
+                    }
+                })?;
+                eprintln!("Percentile distribution exported: {}", path.display());
+            }
+        }
+    }
+
+    let report = settings.render(format, &summary, &output_path)?;
+    write_report(&report, &output_path)
+}
@@ -0,0 +1,758 @@
+//! Serve mode: the reports of a results directory over HTTP, rendered on
+//! request, with uploads of new results.
+
+use super::{is_result_name, result_files};
+use crate::analyze::RunOverview;
+use crate::config::{ReportArgs, ReportSettings};
+use crate::parse::{open_detected, FileFormat};
+use crate::render::{
+    build_index_document, report_json, url_path, IndexEntry, OutputFormat, DEFAULT_INDEX_TITLE,
+};
+use crate::Error;
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Flags of `k6r serve`
+#[derive(Args, Clone)]
+#[non_exhaustive]
+pub struct ServeArgs {
+    /// Directory of k6 result files; uploads are saved here
+    #[arg(value_name = "DIR", default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Address to listen on; 0.0.0.0:PORT makes the server reachable from other machines
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    pub listen: SocketAddr,
+
+    /// How to build the reports
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+/// Largest upload accepted, compressed or not
+const MAX_UPLOAD_BYTES: usize = 512 * 1024 * 1024;
+
+/// Largest header block of a form part
+const MAX_PART_HEADER_BYTES: u64 = 16 * 1024;
+
+/// Added to the HTML index page so results can be uploaded from a browser
+const UPLOAD_FORM: &str = "<h2>Upload</h2>\n\
+<form method=\"post\" action=\"reports\" enctype=\"multipart/form-data\">\n\
+<input type=\"file\" name=\"file\" required>\n\
+<button>Upload</button>\n\
+</form>\n";
+
+type Reply = tiny_http::Response<std::io::Cursor<Vec<u8>>>;
+/// Files that could not be read as results, with the reason
+type Skipped = Vec<(String, String)>;
+
+/// Serves the reports of a directory until the process is stopped
+pub fn run(args: ServeArgs) -> Result<(), Error> {
+    let settings = ReportSettings::load(args.report)?;
+    // Fail now rather than on every request if the directory is unreadable
+    result_files(&args.dir)?;
+    let server = tiny_http::Server::http(args.listen).map_err(|e| Error::Serve(e.to_string()))?;
+    let listen = server.server_addr().to_ip().unwrap_or(args.listen);
+    eprintln!(
+        "Serving reports of {} at http://{}/ (Ctrl-C to stop)",
+        args.dir.display(),
+        listen
+    );
+    ReportServer::new(args.dir, settings, listen).run(&server);
+    Ok(())
+}
+
+/// Renders the results of a directory on request and saves uploads into it
+struct ReportServer {
+    dir: PathBuf,
+    settings: ReportSettings,
+    /// The address the server listens on, which requests must be addressed to
+    listen: SocketAddr,
+    /// Overviews of the files read so far, with the modification time they are of
+    overviews: HashMap<PathBuf, (SystemTime, Result<RunOverview, String>)>,
+}
+
+/// A run as listed by `GET /reports`
+#[derive(Serialize)]
+struct RunData<'a> {
+    name: &'a str,
+    href: String,
+    #[serde(flatten)]
+    overview: &'a RunOverview,
+}
+
+#[derive(Serialize)]
+struct SkippedData<'a> {
+    name: &'a str,
+    error: &'a str,
+}
+
+impl ReportServer {
+    fn new(dir: PathBuf, settings: ReportSettings, listen: SocketAddr) -> Self {
+        ReportServer {
+            dir,
+            settings,
+            listen,
+            overviews: HashMap::new(),
+        }
+    }
+
+    /// Answers requests one at a time
+    fn run(&mut self, server: &tiny_http::Server) {
+        for mut request in server.incoming_requests() {
+            let response = self.respond(&mut request);
+            eprintln!(
+                "{} {} {}",
+                request.method(),
+                request.url(),
+                response.status_code().0
+            );
+            // The client may have gone away; the next request is unaffected
+            let _ = request.respond(response);
+        }
+    }
+
+    fn respond(&mut self, request: &mut tiny_http::Request) -> Reply {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let method = request.method().clone();
+        // Web pages must not reach the server through DNS rebinding or
+        // upload to it from another site
+        let host = request_header(request, "Host").unwrap_or_default();
+        if !is_own_host(&host, self.listen) {
+            return plain_reply(403, &format!("Host '{}' is not served here", host));
+        }
+        let origin = request_header(request, "Origin");
+        if method == tiny_http::Method::Post
+            && origin.is_some_and(|origin| origin != format!("http://{}", host))
+        {
+            return plain_reply(403, "Uploads from other sites are not allowed");
+        }
+        match (&method, path) {
+            (tiny_http::Method::Get, "/") => self.index_page(),
+            (tiny_http::Method::Get, "/reports") => self.index_json(),
+            (tiny_http::Method::Post, "/reports") => self.upload(request, query),
+            (tiny_http::Method::Get, path) if path.starts_with("/reports/") => {
+                let name = percent_decode(&path["/reports/".len()..]);
+                let format = query_param(query, "format");
+                self.report(name.as_deref().unwrap_or(""), format.as_deref())
+            }
+            (_, "/" | "/reports") => plain_reply(405, "Method not allowed"),
+            _ if path.starts_with("/reports/") => plain_reply(405, "Method not allowed"),
+            _ => plain_reply(404, "Not found"),
+        }
+    }
+
+    /// The runs in the directory, and the files that could not be read
+    fn runs(&mut self) -> Result<(Vec<IndexEntry>, Skipped), Error> {
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        for path in result_files(&self.dir)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            match self.overview(&path) {
+                Ok(overview) => {
+                    let href = format!("reports/{}", url_path(&name));
+                    entries.push(IndexEntry::new(name.clone(), href, overview))
+                }
+                Err(e) => skipped.push((name.to_string(), e)),
+            }
+        }
+        Ok((entries, skipped))
+    }
+
+    /// The overview of a result file, read again only once the file changes
+    fn overview(&mut self, path: &Path) -> Result<RunOverview, String> {
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| e.to_string())?;
+        if let Some((time, overview)) = self.overviews.get(path) {
+            if *time == modified {
+                return overview.clone();
+            }
+        }
+        let overview = self
+            .settings
+            .load_file(path, None)
+            .map(|summary| RunOverview::of(&summary))
+            .map_err(|e| e.to_string());
+        self.overviews
+            .insert(path.to_path_buf(), (modified, overview.clone()));
+        overview
+    }
+
+    fn index_page(&mut self) -> Reply {
+        let (entries, skipped) = match self.runs() {
+            Ok(runs) => runs,
+            Err(e) => return plain_reply(500, &e.to_string()),
+        };
+        let document = build_index_document(
+            DEFAULT_INDEX_TITLE,
+            &entries,
+            &skipped,
+            &self.settings.options.units,
+        );
+        let page = OutputFormat::Html
+            .renderer()
+            .render(&document)
+            .replace("</body>", &format!("{}</body>", UPLOAD_FORM));
+        reply(200, "text/html; charset=utf-8", page)
+    }
+
+    fn index_json(&mut self) -> Reply {
+        let (entries, skipped) = match self.runs() {
+            Ok(runs) => runs,
+            Err(e) => return plain_reply(500, &e.to_string()),
+        };
+        let runs: Vec<RunData> = entries
+            .iter()
+            .map(|entry| RunData {
+                name: &entry.name,
+                href: format!("/{}", entry.href),
+                overview: &entry.overview,
+            })
+            .collect();
+        let skipped: Vec<SkippedData> = skipped
+            .iter()
+            .map(|(name, error)| SkippedData { name, error })
+            .collect();
+        let body = serde_json::json!({ "runs": runs, "skipped": skipped });
+        reply(200, "application/json", body.to_string())
+    }
+
+    /// A report as an HTML page, or a Markdown, text or JSON download
+    fn report(&self, name: &str, format: Option<&str>) -> Reply {
+        let path = self.dir.join(name);
+        if !is_result_name(name) || !path.is_file() {
+            return plain_reply(404, &format!("No results named '{}'", name));
+        }
+        let format = match format.unwrap_or("html") {
+            "json" => None,
+            format => match OutputFormat::from_str(format, true) {
+                Ok(OutputFormat::Term) | Err(_) => {
+                    return plain_reply(
+                        400,
+                        &format!(
+                            "Unknown format '{}': use html, markdown, text or json",
+                            format
+                        ),
+                    )
+                }
+                Ok(format) => Some(format),
+            },
+        };
+        let summary = match self.settings.load_file(&path, None) {
+            Ok(summary) => summary,
+            Err(e) => return plain_reply(422, &e.to_string()),
+        };
+        let (body, content_type, extension) = match format {
+            None => (
+                report_json(&summary, &self.settings.report_options(&summary)),
+                "application/json",
+                "json",
+            ),
+            Some(format) => {
+                let output_path = path.with_extension(format.extension());
+                let report = match self.settings.render(format, &summary, &output_path) {
+                    Ok(report) => report,
+                    Err(e) => return plain_reply(500, &e.to_string()),
+                };
+                let content_type = match format {
+                    OutputFormat::Html => "text/html; charset=utf-8",
+                    OutputFormat::Markdown => "text/markdown; charset=utf-8",
+                    _ => "text/plain; charset=utf-8",
+                };
+                (report, content_type, format.extension())
+            }
+        };
+        let response = reply(200, content_type, body);
+        if format == Some(OutputFormat::Html) {
+            return response;
+        }
+        let file_name = Path::new(name).with_extension(extension);
+        response.with_header(header(
+            "Content-Disposition",
+            &format!(
+                "attachment; filename*=UTF-8''{}",
+                url_path(&file_name.to_string_lossy())
+            ),
+        ))
+    }
+
+    /// Saves an uploaded result file once it reads as k6 results. The file is
+    /// the request body, named by `?name=`, or the file of a browser form.
+    fn upload(&mut self, request: &mut tiny_http::Request, query: &str) -> Reply {
+        if request
+            .body_length()
+            .is_some_and(|length| length > MAX_UPLOAD_BYTES)
+        {
+            return plain_reply(413, "The upload is too large");
+        }
+        let content_type = request_header(request, "Content-Type").unwrap_or_default();
+        let from_form = content_type.starts_with("multipart/form-data");
+        let name = if from_form {
+            None
+        } else {
+            query_param(query, "name")
+        };
+        if let Some(name) = name.as_deref().filter(|name| !is_result_name(name)) {
+            return plain_reply(400, &format!("'{}' is not a name for results", name));
+        }
+
+        // Checked under a hidden name, which listings skip, before it appears
+        let temp = self.dir.join(format!(".k6r-upload-{}", std::process::id()));
+        let name = match save_upload(request, &content_type, &temp) {
+            Ok(form_name) if from_form => form_name,
+            Ok(_) => name,
+            Err(response) => {
+                let _ = std::fs::remove_file(&temp);
+                return response;
+            }
+        };
+        if let Some(name) = name.as_deref().filter(|name| !is_result_name(name)) {
+            let _ = std::fs::remove_file(&temp);
+            return plain_reply(400, &format!("'{}' is not a name for results", name));
+        }
+        let summary = match self.settings.load_file(&temp, None) {
+            Ok(summary) => summary,
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp);
+                return plain_reply(400, &e.to_string());
+            }
+        };
+        let name = name.unwrap_or_else(|| {
+            let format = open_detected(&temp, None).map(|input| input.detection.format);
+            match format {
+                Ok(FileFormat::Jsonl) => "upload.jsonl",
+                Ok(FileFormat::Csv) => "upload.csv",
+                _ => "upload.json",
+            }
+            .to_string()
+        });
+        let name = available_name(&self.dir, &name);
+        if let Err(e) = std::fs::rename(&temp, self.dir.join(&name)) {
+            let _ = std::fs::remove_file(&temp);
+            return plain_reply(500, &format!("Failed to save the upload: {}", e));
+        }
+        eprintln!("Saved upload as {}", self.dir.join(&name).display());
+
+        let location = format!("/reports/{}", url_path(&name));
+        // A browser form goes on to the report, API clients get the run
+        let from_browser =
+            request_header(request, "Accept").is_some_and(|accept| accept.contains("text/html"));
+        let response = if from_browser {
+            reply(303, "text/plain; charset=utf-8", "")
+        } else {
+            let overview = RunOverview::of(&summary);
+            let run = RunData {
+                name: &name,
+                href: location.clone(),
+                overview: &overview,
+            };
+            reply(
+                201,
+                "application/json",
+                serde_json::to_string(&run).unwrap_or_default(),
+            )
+        };
+        response.with_header(header("Location", &location))
+    }
+}
+
+fn reply(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Reply {
+    tiny_http::Response::from_data(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", content_type))
+}
+
+fn plain_reply(status: u16, message: &str) -> Reply {
+    reply(
+        status,
+        "text/plain; charset=utf-8",
+        format!("{}\n", message),
+    )
+}
+
+/// A response header; names and values are ASCII, file names percent-encoded
+fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name, value).expect("ASCII header")
+}
+
+fn request_header(request: &tiny_http::Request, name: &'static str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.to_string())
+}
+
+/// Whether a `Host` header names this server: localhost or the address it
+/// listens on, at its port. Any IP address is accepted when it listens on all
+/// of them, but never a domain name, which a web page could point anywhere.
+fn is_own_host(host: &str, listen: SocketAddr) -> bool {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => (name, port.parse().ok()),
+        _ => (host, Some(80)),
+    };
+    if port != Some(listen.port()) {
+        return false;
+    }
+    if name.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    match name.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(std::net::IpAddr::V4(ip)) if ip.is_loopback() => true,
+        Ok(std::net::IpAddr::V6(ip)) if ip.is_loopback() => true,
+        Ok(ip) => ip == listen.ip() || listen.ip().is_unspecified(),
+        Err(_) => false,
+    }
+}
+
+/// Streams an uploaded file to `path`: the request body, or the file of a
+/// browser form. Returns the name the form gave the file, if any.
+fn save_upload(
+    request: &mut tiny_http::Request,
+    content_type: &str,
+    path: &Path,
+) -> Result<Option<String>, Reply> {
+    let mut file = std::fs::File::create(path)
+        .map_err(|e| plain_reply(500, &format!("Failed to save the upload: {}", e)))?;
+    // One byte past the limit tells a body at the limit from a longer one
+    let mut body = std::io::BufReader::new(request.as_reader().take(MAX_UPLOAD_BYTES as u64 + 1));
+    let copied = if content_type.starts_with("multipart/form-data") {
+        copy_multipart_file(&mut body, content_type, &mut file)
+    } else {
+        std::io::copy(&mut body, &mut file).map(|_| Some(None))
+    };
+    if body.get_ref().limit() == 0 {
+        return Err(plain_reply(413, "The upload is too large"));
+    }
+    match copied {
+        Ok(Some(name)) => Ok(name),
+        Ok(None) => Err(plain_reply(400, "The form holds no file")),
+        Err(e) => Err(plain_reply(
+            400,
+            &format!("Failed to save the upload: {}", e),
+        )),
+    }
+}
+
+/// The decoded value of a query string parameter
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key == name).then(|| percent_decode(&value.replace('+', " ")))?
+    })
+}
+
+/// Decodes `%XX` escapes; `None` for bad escapes or text that is not UTF-8
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Streams the first file of a multipart/form-data body to `out`. `None` if
+/// the form holds no file, else the name the form gave it, if any.
+fn copy_multipart_file(
+    body: &mut impl BufRead,
+    content_type: &str,
+    out: &mut impl Write,
+) -> std::io::Result<Option<Option<String>>> {
+    let Some(boundary) = content_type
+        .split(';')
+        .find_map(|param| param.trim().strip_prefix("boundary="))
+    else {
+        return Ok(None);
+    };
+    let delimiter = format!("\r\n--{}", boundary.trim_matches('"'));
+    let delimiter = delimiter.as_bytes();
+    // The first delimiter may open the body, without a line break before it
+    if !copy_until(body, &mut std::io::sink(), &delimiter[2..])? {
+        return Ok(None);
+    }
+    // Each part is headers, a blank line and the content, up to the next
+    // delimiter; `--` after a delimiter ends the form
+    loop {
+        let mut headers = Vec::new();
+        let mut header_block = body.by_ref().take(MAX_PART_HEADER_BYTES);
+        if !copy_until(&mut header_block, &mut headers, b"\r\n\r\n")? || headers.starts_with(b"--")
+        {
+            return Ok(None);
+        }
+        let headers = String::from_utf8_lossy(&headers);
+        let file_name = headers.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if !name.trim().eq_ignore_ascii_case("content-disposition") {
+                return None;
+            }
+            let (_, file_name) = value.split_once("filename=\"")?;
+            Some(file_name.split('"').next().unwrap_or_default().to_string())
+        });
+        if let Some(file_name) = file_name {
+            let found = copy_until(body, out, delimiter)?;
+            return Ok(found.then(|| (!file_name.is_empty()).then_some(file_name)));
+        }
+        if !copy_until(body, &mut std::io::sink(), delimiter)? {
+            return Ok(None);
+        }
+    }
+}
+
+/// Copies `reader` to `out` up to `delimiter`, which is read but not copied.
+/// `false` if the reader ends first, having copied all of it.
+fn copy_until(
+    reader: &mut impl BufRead,
+    out: &mut impl Write,
+    delimiter: &[u8],
+) -> std::io::Result<bool> {
+    // Read bytes that could begin the delimiter, held back until it is known
+    let mut held = Vec::new();
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            out.write_all(&held)?;
+            return Ok(false);
+        }
+        let (held_len, chunk_len) = (held.len(), chunk.len());
+        held.extend_from_slice(chunk);
+        if let Some(index) = find_bytes(&held, delimiter) {
+            out.write_all(&held[..index])?;
+            // Leave what follows the delimiter to the next read
+            reader.consume(index + delimiter.len() - held_len);
+            return Ok(true);
+        }
+        reader.consume(chunk_len);
+        let keep = held.len().min(delimiter.len() - 1);
+        out.write_all(&held[..held.len() - keep])?;
+        held.drain(..held.len() - keep);
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// `name` if no file in `dir` has it yet, else the first free `stem-N.ext`
+fn available_name(dir: &Path, name: &str) -> String {
+    if !dir.join(name).exists() {
+        return name.to_string();
+    }
+    // Split before the first extension so run.csv.gz becomes run-1.csv.gz
+    let (stem, extension) = match name.find('.') {
+        Some(index) => name.split_at(index),
+        None => (name, ""),
+    };
+    (1..)
+        .map(|n| format!("{}-{}{}", stem, n, extension))
+        .find(|candidate| !dir.join(candidate).exists())
+        .unwrap_or_default()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends a request to the server and returns the status, headers and body.
+    /// It is addressed to localhost unless `head` gives a `Host`.
+    fn http(addr: SocketAddr, head: &str, body: &[u8]) -> (u16, String, String) {
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        if !head.contains("\r\nHost:") {
+            write!(stream, "{}\r\nHost: localhost:{}", head, addr.port()).unwrap();
+        } else {
+            write!(stream, "{}", head).unwrap();
+        }
+        write!(
+            stream,
+            "\r\nConnection: close\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (headers, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = headers[9..12].parse().unwrap();
+        (status, headers.to_string(), body.to_string())
+    }
+
+    #[test]
+    fn test_serve_reports_and_uploads() {
+        let jsonl = include_str!("../../tests/fixtures/k6-run.jsonl");
+        let dir = std::env::temp_dir().join(format!("k6r-serve-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("k6-run.jsonl"), jsonl).unwrap();
+        std::fs::write(dir.join("bad.json"), "not results").unwrap();
+
+        let settings = ReportSettings::load(ReportArgs::default()).unwrap();
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let served = dir.clone();
+        std::thread::spawn(move || ReportServer::new(served, settings, addr).run(&server));
+
+        let (status, _, body) = http(addr, "GET /reports HTTP/1.1", b"");
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""name":"k6-run.jsonl""#), "{}", body);
+        assert!(body.contains(r#""skipped":[{"error""#), "{}", body);
+
+        let (status, headers, body) = http(
+            addr,
+            "GET /reports/k6-run.jsonl?format=markdown HTTP/1.1",
+            b"",
+        );
+        assert_eq!(status, 200);
+        assert!(
+            headers.contains("filename*=UTF-8''k6-run.md"),
+            "{}",
+            headers
+        );
+        assert!(body.starts_with("# K6 Load Test Report"), "{}", body);
+        let (status, _, body) = http(addr, "GET /reports/k6-run.jsonl?format=json HTTP/1.1", b"");
+        assert_eq!(status, 200);
+        assert!(body.contains(r#""name": "http_req_duration""#), "{}", body);
+        let (status, _, _) = http(addr, "GET /reports/..%2Fk6-run.jsonl HTTP/1.1", b"");
+        assert_eq!(status, 404);
+        let (status, _, _) = http(addr, "GET /reports/bad.json HTTP/1.1", b"");
+        assert_eq!(status, 422);
+
+        let (status, headers, body) = http(
+            addr,
+            "POST /reports?name=run.jsonl HTTP/1.1",
+            jsonl.as_bytes(),
+        );
+        assert_eq!(status, 201, "{}", body);
+        assert!(
+            headers.contains("Location: /reports/run.jsonl"),
+            "{}",
+            headers
+        );
+        assert!(body.contains(r#""error_rate":"#), "{}", body);
+        assert!(dir.join("run.jsonl").is_file());
+
+        let form = format!(
+            "--XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"run.jsonl\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n{}\r\n--XyZ--\r\n",
+            jsonl
+        );
+        let (status, headers, _) = http(
+            addr,
+            &format!(
+                "POST /reports HTTP/1.1\r\nAccept: text/html\r\n\
+                 Origin: http://localhost:{}\r\n\
+                 Content-Type: multipart/form-data; boundary=XyZ",
+                addr.port()
+            ),
+            form.as_bytes(),
+        );
+        assert_eq!(status, 303);
+        assert!(
+            headers.contains("Location: /reports/run-1.jsonl"),
+            "{}",
+            headers
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("run-1.jsonl")).unwrap(),
+            jsonl
+        );
+
+        let (status, _, _) = http(addr, "POST /reports HTTP/1.1", b"garbage");
+        assert_eq!(status, 400);
+        let (status, _, _) = http(addr, "GET / HTTP/1.1\r\nHost: attacker.example", b"");
+        assert_eq!(status, 403);
+        let (status, _, _) = http(
+            addr,
+            "POST /reports?name=evil.jsonl HTTP/1.1\r\nOrigin: http://attacker.example",
+            jsonl.as_bytes(),
+        );
+        assert_eq!(status, 403);
+        assert!(!dir.join("evil.jsonl").exists());
+        let (status, _, _) = http(addr, "POST /reports?name=..%2Frun.json HTTP/1.1", b"{}");
+        assert_eq!(status, 400);
+
+        let (status, _, body) = http(addr, "GET / HTTP/1.1", b"");
+        assert_eq!(status, 200);
+        assert!(
+            body.contains(r#"<a href="reports/run-1.jsonl">"#),
+            "{}",
+            body
+        );
+        assert!(body.contains("<form"), "{}", body);
+        assert_eq!(result_files(&dir).unwrap().len(), 4);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_serve_helpers() {
+        assert_eq!(
+            percent_decode("run%201.json").as_deref(),
+            Some("run 1.json")
+        );
+        assert_eq!(percent_decode("run%2"), None);
+        assert_eq!(
+            query_param("a=1&name=my+run.json&format=json", "name").as_deref(),
+            Some("my run.json")
+        );
+        assert_eq!(query_param("format=json", "name"), None);
+
+        let body = b"--b\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\nhi\r\n\
+            --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.json\"\r\n\r\n{\r\n-}\r\n--b--\r\n";
+        // A small buffer splits the delimiters across reads
+        let multipart = |body: &[u8]| {
+            let mut file = Vec::new();
+            let mut reader = std::io::BufReader::with_capacity(3, body);
+            copy_multipart_file(&mut reader, "multipart/form-data; boundary=b", &mut file)
+                .unwrap()
+                .map(|name| (name, file))
+        };
+        assert_eq!(
+            multipart(body),
+            Some((Some("a.json".to_string()), b"{\r\n-}".to_vec()))
+        );
+        assert_eq!(multipart(b"--b--\r\n"), None);
+        assert_eq!(
+            multipart(b"--b\r\nContent-Disposition: form-data; filename=\"a\"\r\n\r\n{}"),
+            None
+        );
+
+        let localhost: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        assert!(is_own_host("localhost:8080", localhost));
+        assert!(is_own_host("127.0.0.1:8080", localhost));
+        assert!(is_own_host("[::1]:8080", localhost));
+        assert!(!is_own_host("localhost:9090", localhost));
+        assert!(!is_own_host("localhost", localhost));
+        assert!(!is_own_host("10.0.0.5:8080", localhost));
+        assert!(!is_own_host("attacker.example:8080", localhost));
+        assert!(!is_own_host("", localhost));
+        let everywhere: SocketAddr = "0.0.0.0:8080".parse().unwrap();
+        assert!(is_own_host("10.0.0.5:8080", everywhere));
+        assert!(!is_own_host("build-box:8080", everywhere));
+
+        assert!(is_result_name("run.csv.gz"));
+        assert!(!is_result_name("../run.json"));
+        assert!(!is_result_name("run.html"));
+        assert_eq!(
+            available_name(Path::new("/nonexistent"), "run.csv.gz"),
+            "run.csv.gz"
+        );
+    }
+}
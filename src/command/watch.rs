@@ -0,0 +1,351 @@
+//! Watch mode: reports rendered again as their inputs, the config, the
+//! template or the script change.
+
+use super::batch::{default_index_format, index_files, report_names};
+use super::report::{self, InputArgs};
+use super::{is_newer, is_result_file, result_files};
+use crate::config::{config_path, report_file, ReportArgs, ReportSettings};
+use crate::parse::STDIO_PATH;
+use crate::render::OutputFormat;
+use crate::Error;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long a file must be left alone before its change counts, so a file
+/// being written or an editor's save sequence triggers a single render
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+type WatchEvents = Receiver<notify::Result<Event>>;
+
+/// Renders the report again whenever an input or a settings file changes,
+/// or, for a directory, every result file in it as it is added or changed
+pub fn run(args: &InputArgs, report_args: &ReportArgs) -> Result<(), Error> {
+    let (sender, events) = mpsc::channel();
+    let watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    match &args.input {
+        Some(dir) if dir.is_dir() => watch_directory(args, report_args, dir, watcher, &events),
+        _ => watch_inputs(args, report_args, watcher, &events),
+    }
+}
+
+fn watch_error(e: notify::Error) -> Error {
+    Error::Watch(e.to_string())
+}
+
+/// A path as change events name it: the canonical directory and the file name
+fn watch_key(path: &Path) -> Option<PathBuf> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    Some(dir.canonicalize().ok()?.join(path.file_name()?))
+}
+
+/// Waits for changes to files that `is_relevant` accepts, then until they
+/// have been left alone for [`WATCH_DEBOUNCE`]
+fn next_changes(
+    events: &WatchEvents,
+    is_relevant: impl Fn(&Path) -> bool,
+) -> Result<BTreeSet<PathBuf>, Error> {
+    let stopped = || Error::Watch("the watcher stopped".to_string());
+    let mut changed = BTreeSet::new();
+    let mut deadline: Option<Instant> = None;
+    loop {
+        let event = match deadline {
+            None => events.recv().map_err(|_| stopped())?,
+            Some(deadline) => {
+                match events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => return Ok(changed),
+                    Err(RecvTimeoutError::Disconnected) => return Err(stopped()),
+                }
+            }
+        };
+        let event = event.map_err(watch_error)?;
+        // Reads are not changes, and k6r reads every file it watches
+        if matches!(event.kind, EventKind::Access(_)) {
+            continue;
+        }
+        let mut paths = event
+            .paths
+            .into_iter()
+            .filter(|path| is_relevant(path))
+            .peekable();
+        if paths.peek().is_some() {
+            changed.extend(paths);
+            deadline = Some(Instant::now() + WATCH_DEBOUNCE);
+        }
+    }
+}
+
+/// Re-renders the report whenever an input or a settings file changes
+fn watch_inputs(
+    args: &InputArgs,
+    report_args: &ReportArgs,
+    mut watcher: RecommendedWatcher,
+    events: &WatchEvents,
+) -> Result<(), Error> {
+    let (input, output) = args.positional_paths();
+    let inputs: Vec<&PathBuf> = [input, args.summary.as_ref(), args.points.as_ref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    if inputs.iter().any(|path| *path == Path::new(STDIO_PATH)) {
+        return Err(Error::Watch("stdin cannot be watched".to_string()));
+    }
+
+    let mut watched_dirs: Vec<PathBuf> = Vec::new();
+    loop {
+        let sources = match ReportSettings::load(report_args.clone()) {
+            Ok(settings) => {
+                // A report on the terminal is redrawn in place
+                let (_, output_path) = settings.output(output.map(PathBuf::as_path), inputs[0]);
+                if output_path == Path::new(STDIO_PATH) && std::io::stdout().is_terminal() {
+                    print!("\x1b[H\x1b[2J");
+                }
+                if let Err(e) = report::run(args, &settings) {
+                    eprintln!("Error: {}", e);
+                }
+                settings.sources
+            }
+            // Keep watching the config so fixing it renders again
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                config_path(report_args).into_iter().collect()
+            }
+        };
+
+        let files: Vec<PathBuf> = inputs
+            .iter()
+            .map(|path| path.as_path())
+            .chain(sources.iter().map(PathBuf::as_path))
+            .filter_map(watch_key)
+            .collect();
+        // Watching directories rather than files follows editors that save by
+        // replacing the file
+        let mut dirs: Vec<PathBuf> = files
+            .iter()
+            .filter_map(|file| file.parent().map(Path::to_path_buf))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        for dir in &watched_dirs {
+            if !dirs.contains(dir) {
+                let _ = watcher.unwatch(dir);
+            }
+        }
+        for dir in &dirs {
+            if !watched_dirs.contains(dir) {
+                watcher
+                    .watch(dir, RecursiveMode::NonRecursive)
+                    .map_err(watch_error)?;
+            }
+        }
+        watched_dirs = dirs;
+
+        eprintln!(
+            "Watching {} file(s) for changes (Ctrl-C to stop)",
+            files.len()
+        );
+        let changed = next_changes(events, |path| files.iter().any(|file| file == path))?;
+        for path in changed {
+            eprintln!("Changed: {}", path.display());
+        }
+    }
+}
+
+/// Renders a report for every result file in `dir` that has none or an
+/// outdated one, then for each one that is added or changed
+fn watch_directory(
+    args: &InputArgs,
+    report_args: &ReportArgs,
+    dir: &Path,
+    mut watcher: RecommendedWatcher,
+    events: &WatchEvents,
+) -> Result<(), Error> {
+    let output_dir = args.output.as_deref().unwrap_or(dir);
+    std::fs::create_dir_all(output_dir).map_err(|source| Error::Write {
+        path: output_dir.to_path_buf(),
+        source,
+    })?;
+    let dir = dir.canonicalize().map_err(|source| Error::Read {
+        path: dir.to_path_buf(),
+        source,
+    })?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+
+    let mut settings = ReportSettings::load(report_args.clone())?;
+    let mut sources = watch_sources(&mut watcher, &settings)?;
+    let render = |settings: &ReportSettings, input: &Path, output_path: &Path| {
+        let format = settings.format.unwrap_or_default();
+        if let Err(e) = report_file(settings, input, args.input_format, format, output_path) {
+            eprintln!("Skipped {}: {}", input.display(), e);
+        }
+    };
+    let format = |settings: &ReportSettings| settings.format.unwrap_or_default();
+    let mut outputs = report_paths(&dir, output_dir, format(&settings))?;
+    let mut up_to_date = 0;
+    for (input, output_path) in &outputs {
+        if is_newer(output_path, input) {
+            up_to_date += 1;
+        } else {
+            render(&settings, input, output_path);
+        }
+    }
+    if up_to_date > 0 {
+        eprintln!("{} report(s) already up to date", up_to_date);
+    }
+
+    loop {
+        eprintln!(
+            "Watching {} for new results (Ctrl-C to stop)",
+            dir.display()
+        );
+        let changed = next_changes(events, |path| {
+            sources.iter().any(|source| source == path)
+                || (path.parent() == Some(dir.as_path()) && is_result_file(path))
+        })?;
+        let Some(source) = changed.iter().find(|path| sources.contains(path)) else {
+            let previous = std::mem::replace(
+                &mut outputs,
+                report_paths(&dir, output_dir, format(&settings))?,
+            );
+            // A new input can change the report name of one sharing its name
+            for (input, output_path) in &outputs {
+                if changed.contains(input) || previous.get(input) != Some(output_path) {
+                    render(&settings, input, output_path);
+                }
+            }
+            continue;
+        };
+        // New settings apply to every report
+        eprintln!("Changed: {}", source.display());
+        match ReportSettings::load(report_args.clone()) {
+            Ok(reloaded) => {
+                settings = reloaded;
+                sources = watch_sources(&mut watcher, &settings)?;
+                outputs = report_paths(&dir, output_dir, format(&settings))?;
+                for (input, output_path) in &outputs {
+                    render(&settings, input, output_path);
+                }
+            }
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+}
+
+/// The result files in `dir` and their report paths in `output_dir`, named as
+/// in batch mode so that no two share a report
+fn report_paths(
+    dir: &Path,
+    output_dir: &Path,
+    format: OutputFormat,
+) -> Result<BTreeMap<PathBuf, PathBuf>, Error> {
+    let inputs = result_files(dir)?;
+    let index_names: Vec<String> = index_files(vec![default_index_format(format)])
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    let names = report_names(&inputs, format, &index_names);
+    let paths = names.into_iter().map(|name| output_dir.join(name));
+    Ok(inputs.into_iter().zip(paths).collect())
+}
+
+/// Watches the settings files and returns them as change events name them
+fn watch_sources(
+    watcher: &mut RecommendedWatcher,
+    settings: &ReportSettings,
+) -> Result<Vec<PathBuf>, Error> {
+    let sources: Vec<PathBuf> = settings
+        .sources
+        .iter()
+        .filter_map(|p| watch_key(p))
+        .collect();
+    for dir in sources.iter().filter_map(|source| source.parent()) {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+    }
+    Ok(sources)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_key_resolves_missing_files() {
+        let dir = std::env::temp_dir().join(format!("k6r-watch-key-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(
+            watch_key(&dir.join("missing.json")),
+            Some(dir.canonicalize().unwrap().join("missing.json"))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_next_changes_ignores_reads_and_debounces() {
+        use notify::event::{AccessKind, CreateKind, ModifyKind};
+
+        let (sender, events) = mpsc::channel();
+        let event = |kind, path: &str| Ok(Event::new(kind).add_path(PathBuf::from(path)));
+        sender
+            .send(event(EventKind::Access(AccessKind::Any), "/r/a.json"))
+            .unwrap();
+        sender
+            .send(event(EventKind::Create(CreateKind::File), "/r/report.md"))
+            .unwrap();
+        sender
+            .send(event(EventKind::Modify(ModifyKind::Any), "/r/a.json"))
+            .unwrap();
+        sender
+            .send(event(EventKind::Create(CreateKind::File), "/r/b.json"))
+            .unwrap();
+
+        let changed = next_changes(&events, |path| path.extension() == Some("json".as_ref()));
+        assert_eq!(
+            changed.unwrap(),
+            BTreeSet::from([PathBuf::from("/r/a.json"), PathBuf::from("/r/b.json")])
+        );
+
+        drop(sender);
+        assert!(next_changes(&events, |_| true).is_err());
+    }
+
+    #[test]
+    fn test_watch_report_paths() {
+        let dir = std::env::temp_dir().join(format!("k6r-watch-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["run.json", "run.csv", "index.json", "run.md"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let paths = report_paths(&dir, Path::new("out"), OutputFormat::Markdown).unwrap();
+        let names: Vec<(String, &Path)> = paths
+            .iter()
+            .map(|(input, output)| {
+                let input = input.file_name().unwrap().to_string_lossy().into_owned();
+                (input, output.as_path())
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("index.json".to_string(), Path::new("out/index.json.md")),
+                ("run.csv".to_string(), Path::new("out/run.csv.md")),
+                ("run.json".to_string(), Path::new("out/run.json.md")),
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,727 @@
+//! Report settings: the flags every report command shares, the `k6r.toml`
+//! config file of project defaults, and [`ReportSettings`], which merges the
+//! two with the script's options and renders reports with the result.
+
+use crate::analyze::{
+    apply_thresholds, default_trend_stats, merge_script_options, TimeBound, WindowOptions,
+};
+use crate::parse::{open_detected, parse_script_options, FileFormat, ParseOptions, STDIO_PATH};
+use crate::render::{
+    build_document, generate_report, render_template, MetricRoute, OutputFormat, Renderer,
+    ReportOptions, Terminal, TimeUnit, Units, DEFAULT_TITLE,
+};
+use crate::{Error, K6Summary, Section, TagFilter, TestOptions, TrendStat, Unit};
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// =============================================================================
+// Report Flags
+// =============================================================================
+
+/// How to build a report, shared by every command that writes one
+#[derive(Args, Clone, Default)]
+#[non_exhaustive]
+pub struct ReportArgs {
+    /// Report defaults file [default: the nearest k6r.toml in this or a parent directory]
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Output format of the report
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Render the report with this template instead of the built-in layout
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+
+    /// k6 script whose `export const options` provides scenarios and thresholds
+    #[arg(long, value_name = "SCRIPT")]
+    pub script: Option<PathBuf>,
+
+    /// Fail on malformed JSONL or CSV lines instead of skipping them
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Trend stats to compute and display, like k6's summaryTrendStats
+    /// [default: the summary's summaryTrendStats, else avg,min,med,max,p(90),p(95),p(99)]
+    #[arg(long, value_name = "STATS", value_delimiter = ',')]
+    pub trend_stats: Vec<TrendStat>,
+
+    /// Time unit for durations [default: the summary's summaryTimeUnit, else auto]
+    #[arg(long, value_enum)]
+    pub time_unit: Option<TimeUnit>,
+
+    /// Number of decimal places in formatted values [default: 2]
+    #[arg(long, value_name = "N")]
+    pub precision: Option<usize>,
+
+    /// Declare the unit of a custom metric: ms, s, bytes or count (repeatable)
+    #[arg(long = "unit", value_name = "METRIC=UNIT", value_parser = parse_metric_unit)]
+    pub units: Vec<(String, Unit)>,
+
+    /// Only aggregate points from this offset into the run (e.g. 30s, or -2m
+    /// from the end) or RFC 3339 time on
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub from: Option<TimeBound>,
+
+    /// Only aggregate points up to this offset or RFC 3339 time
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    pub to: Option<TimeBound>,
+
+    /// Only aggregate the plateau of the vus series, skipping ramp-up and ramp-down
+    #[arg(long)]
+    pub auto_steady_state: bool,
+
+    /// Only aggregate points whose tag matches, e.g. scenario=checkout or
+    /// status!=2* (repeatable; `*` and `?` are wildcards)
+    #[arg(long = "tag", value_name = "KEY=VALUE")]
+    pub tags: Vec<TagFilter>,
+
+    /// Only list metrics matching this glob in the metric tables (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Leave metrics matching this glob out of the metric tables (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// List matching metrics in their own section, e.g.
+    /// 'Kafka=kafka_writer_*,kafka_reader_*:count,rate' (repeatable)
+    #[arg(long = "route", value_name = "HEADING=GLOBS[:COLUMNS]")]
+    pub routes: Vec<MetricRoute>,
+
+    /// Report sections to render, in order: summary, configuration, thresholds, http,
+    /// endpoints, timeline, latency, checks, routes, metrics [default: all]
+    #[arg(long, value_name = "SECTIONS", value_delimiter = ',')]
+    pub sections: Vec<Section>,
+
+    /// Report title [default: K6 Load Test Report]
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Add a row to the metadata table under the title, e.g. env=staging (repeatable)
+    #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = parse_meta_entry)]
+    pub meta: Vec<(String, String)>,
+}
+
+fn parse_meta_entry(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", s)),
+    }
+}
+
+fn parse_metric_unit(s: &str) -> Result<(String, Unit), String> {
+    let (metric, unit) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected METRIC=UNIT, got '{}'", s))?;
+    Ok((metric.trim().to_string(), unit.parse()?))
+}
+
+// =============================================================================
+// Configuration File
+// =============================================================================
+
+/// The config file looked for in the current directory and its parents
+pub const CONFIG_FILE_NAME: &str = "k6r.toml";
+
+/// Project defaults from `k6r.toml`; every key is optional and command-line
+/// flags take precedence
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Config {
+    /// Output format of the report
+    pub format: Option<OutputFormat>,
+    /// Report template, relative to the config file
+    pub template: Option<PathBuf>,
+    /// Report title
+    pub title: Option<String>,
+    /// Sections to render, in order
+    pub sections: Option<Vec<Section>>,
+    /// Trend stats to compute and display
+    pub trend_stats: Vec<TrendStat>,
+    /// Units of durations and custom metrics
+    pub units: UnitsConfig,
+    /// Extra thresholds per metric, evaluated like the script's
+    pub thresholds: BTreeMap<String, Vec<String>>,
+    /// Globs of the metrics to list in the metric tables
+    pub include: Vec<String>,
+    /// Globs of the metrics to leave out of the metric tables
+    pub exclude: Vec<String>,
+    /// Metrics listed in sections of their own
+    pub routes: Vec<MetricRoute>,
+    /// Key/value pairs for the report header, in file order
+    #[serde(deserialize_with = "deserialize_meta")]
+    pub meta: Vec<(String, MetaValue)>,
+}
+
+/// The `[units]` table of the config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct UnitsConfig {
+    /// Time unit for durations
+    pub time: Option<TimeUnit>,
+    /// Number of decimal places in formatted values
+    pub precision: Option<usize>,
+    /// Units of custom metrics, like `--unit`
+    pub metrics: BTreeMap<String, Unit>,
+}
+
+/// A value of the `[meta]` table, which TOML may type
+#[derive(Debug, Deserialize)]
+#[serde(untagged, expecting = "a string, number or boolean")]
+#[non_exhaustive]
+pub enum MetaValue {
+    /// A string, shown as it is
+    String(String),
+    /// A whole number
+    Integer(i64),
+    /// A decimal number
+    Float(f64),
+    /// `true` or `false`
+    Boolean(bool),
+}
+
+impl std::fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetaValue::String(s) => write!(f, "{}", s),
+            MetaValue::Integer(n) => write!(f, "{}", n),
+            MetaValue::Float(n) => write!(f, "{}", n),
+            MetaValue::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Reads the `[meta]` table as pairs, keeping the order they are written in
+fn deserialize_meta<'de, D>(deserializer: D) -> Result<Vec<(String, MetaValue)>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct MetaVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for MetaVisitor {
+        type Value = Vec<(String, MetaValue)>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a table of metadata")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                entries.push(entry);
+            }
+            Ok(entries)
+        }
+    }
+
+    deserializer.deserialize_map(MetaVisitor)
+}
+
+/// Parses a config file, prefixing errors with the line and key path they occur at
+pub fn parse_config(content: &str) -> Result<Config, String> {
+    let deserializer = toml::Deserializer::new(content);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        let line = inner
+            .span()
+            .map(|span| content[..span.start].matches('\n').count() + 1);
+        match (line, path.as_str()) {
+            (Some(line), ".") => format!("line {}: {}", line, inner.message()),
+            (Some(line), _) => format!("line {}: {}: {}", line, path, inner.message()),
+            (None, ".") => inner.message().to_string(),
+            (None, _) => format!("{}: {}", path, inner.message()),
+        }
+    })
+}
+
+/// Reads and parses a config file
+pub fn load_config(path: &Path) -> Result<Config, Error> {
+    let content = std::fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse_config(&content).map_err(|message| Error::Config {
+        path: path.to_path_buf(),
+        message,
+    })
+}
+
+/// The config file to use: the given one, else the nearest `k6r.toml`
+pub(crate) fn config_path(args: &ReportArgs) -> Option<PathBuf> {
+    match &args.config {
+        Some(path) => Some(path.clone()),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|dir| find_config(&dir)),
+    }
+}
+
+/// Looks for `k6r.toml` in `dir` and each of its parents
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+// =============================================================================
+// Report Settings
+// =============================================================================
+
+/// The report flags merged with the config file and the script's options
+pub struct ReportSettings {
+    /// From the flags or config; otherwise it depends on where the report goes
+    pub format: Option<OutputFormat>,
+    /// Name and source of the report template
+    template: Option<(String, String)>,
+    script: Option<TestOptions>,
+    /// Extra thresholds from the config file
+    thresholds: BTreeMap<String, Vec<String>>,
+    /// How result files are read
+    pub parse_options: ParseOptions,
+    /// The config, template and script files the settings were read from
+    pub sources: Vec<PathBuf>,
+    /// Whether the flags or config chose the trend stats, over the summary's
+    trend_stats_set: bool,
+    /// Whether the flags or config chose the time unit, over the summary's
+    time_unit_set: bool,
+    /// How reports are built, before a summary's own display options apply
+    pub options: ReportOptions,
+}
+
+impl ReportSettings {
+    /// Merges the flags with the config file they name or the nearest one,
+    /// and reads the template and script
+    pub fn load(mut args: ReportArgs) -> Result<Self, Error> {
+        let config_path = config_path(&args);
+        let config = match &config_path {
+            Some(path) => {
+                let config = load_config(path)?;
+                eprintln!("Using config: {}", path.display());
+                config
+            }
+            None => Config::default(),
+        };
+
+        // Command-line flags override the config file
+        if args.trend_stats.is_empty() {
+            args.trend_stats = config.trend_stats;
+        }
+        args.time_unit = args.time_unit.or(config.units.time);
+        args.precision = args.precision.or(config.units.precision);
+        args.units.splice(0..0, config.units.metrics);
+        if args.include.is_empty() {
+            args.include = config.include;
+        }
+        if args.exclude.is_empty() {
+            args.exclude = config.exclude;
+        }
+        if args.routes.is_empty() {
+            args.routes = config.routes;
+        }
+        if args.sections.is_empty() {
+            args.sections = config.sections.unwrap_or_else(|| Section::ALL.to_vec());
+        }
+        let title = args
+            .title
+            .take()
+            .or(config.title)
+            .unwrap_or_else(|| DEFAULT_TITLE.to_string());
+        let mut meta: Vec<(String, String)> = config
+            .meta
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect();
+        for (key, value) in std::mem::take(&mut args.meta) {
+            match meta.iter_mut().find(|(existing, _)| *existing == key) {
+                Some(entry) => entry.1 = value,
+                None => meta.push((key, value)),
+            }
+        }
+
+        let config_dir = config_path.as_deref().and_then(Path::parent);
+        let template_path = args.template.take().or_else(|| {
+            let path = config.template?;
+            Some(config_dir.map_or(path.clone(), |dir| dir.join(path)))
+        });
+        let mut sources: Vec<PathBuf> = config_path.iter().cloned().collect();
+        sources.extend(template_path.iter().cloned());
+        sources.extend(args.script.iter().cloned());
+        let template = match template_path {
+            Some(path) => {
+                let source = std::fs::read_to_string(&path).map_err(|source| Error::Read {
+                    path: path.clone(),
+                    source,
+                })?;
+                Some((path.display().to_string(), source))
+            }
+            None => None,
+        };
+
+        let script = match &args.script {
+            Some(path) => {
+                let script = std::fs::read_to_string(path).map_err(|source| Error::Read {
+                    path: path.clone(),
+                    source,
+                })?;
+                let options = parse_script_options(&script).map_err(|message| Error::Script {
+                    path: path.clone(),
+                    message,
+                })?;
+                Some(options)
+            }
+            None => None,
+        };
+
+        let trend_stats = match &script {
+            _ if !args.trend_stats.is_empty() => args.trend_stats.clone(),
+            Some(opts) if !opts.summary_trend_stats.is_empty() => opts
+                .summary_trend_stats
+                .iter()
+                .filter_map(|s| s.parse().ok())
+                .collect(),
+            _ => default_trend_stats(),
+        };
+
+        let mut parse_options = ParseOptions {
+            trend_stats: trend_stats.clone(),
+            strict: args.strict,
+            window: WindowOptions::new(args.from, args.to, args.auto_steady_state),
+            tag_filters: args.tags,
+            thresholds: script
+                .as_ref()
+                .map(|opts| {
+                    opts.thresholds
+                        .iter()
+                        .map(|(metric, defs)| {
+                            let exprs = defs.iter().map(|d| d.expression().to_string());
+                            (metric.clone(), exprs.collect())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            ..ParseOptions::default()
+        };
+        for (metric, exprs) in &config.thresholds {
+            let merged = parse_options.thresholds.entry(metric.clone()).or_default();
+            for expr in exprs {
+                if !merged.contains(expr) {
+                    merged.push(expr.clone());
+                }
+            }
+        }
+
+        let mut units = Units::default();
+        units.time_unit = args.time_unit.unwrap_or_default();
+        units.precision = args.precision.unwrap_or(units.precision);
+        units.metric_units = args.units.into_iter().collect();
+
+        let options = ReportOptions {
+            title,
+            meta,
+            sections: args.sections,
+            trend_stats,
+            units,
+            include: args.include,
+            exclude: args.exclude,
+            routes: args.routes,
+        };
+
+        Ok(ReportSettings {
+            format: args.format.or(config.format),
+            template,
+            script,
+            thresholds: config.thresholds,
+            parse_options,
+            sources,
+            trend_stats_set: !args.trend_stats.is_empty(),
+            time_unit_set: args.time_unit.is_some(),
+            options,
+        })
+    }
+
+    /// The report format and where the report for `input` goes, `-` for stdout
+    pub fn output(&self, output: Option<&Path>, input: &Path) -> (OutputFormat, PathBuf) {
+        let stdio = Path::new(STDIO_PATH);
+        // Reports for stdout are for reading there unless a format is chosen
+        let to_stdout = output.map_or(input == stdio, |path| path == stdio);
+        let format = self.format.unwrap_or(if to_stdout {
+            OutputFormat::Term
+        } else {
+            OutputFormat::Markdown
+        });
+        let path = match output {
+            Some(path) => path.to_path_buf(),
+            None if to_stdout || format == OutputFormat::Term => stdio.to_path_buf(),
+            None => input.with_extension(format.extension()),
+        };
+        (format, path)
+    }
+
+    /// Reads a single result file, whatever its format, and completes it
+    pub fn load_file(
+        &self,
+        input: &Path,
+        input_format: Option<FileFormat>,
+    ) -> Result<K6Summary, Error> {
+        let input = open_detected(input, input_format)?;
+        let parse_options = &self.parse_options;
+        if input.detection.format.is_summary() {
+            if !parse_options.tag_filters.is_empty() {
+                return Err(Error::NeedsPoints("Tag filters"));
+            }
+            if parse_options.window.is_set() {
+                return Err(Error::NeedsPoints("Time windows"));
+            }
+        }
+        let mut summary = input.parse(parse_options)?;
+        self.complete(&mut summary);
+        Ok(summary)
+    }
+
+    /// Applies the config thresholds and script options to a parsed summary
+    pub fn complete(&self, summary: &mut K6Summary) {
+        // Points already evaluated these; a summary only has the run's own thresholds
+        apply_thresholds(summary, &self.thresholds);
+
+        for warning in &summary.warnings {
+            eprintln!("Warning: {}", warning);
+        }
+
+        if let Some(script) = &self.script {
+            for metric in script.thresholds.keys() {
+                if !summary.metrics.contains_key(metric) {
+                    eprintln!(
+                        "Warning: no data for threshold metric '{}' in script options",
+                        metric
+                    );
+                }
+            }
+            merge_script_options(summary, script.clone());
+        }
+    }
+
+    /// Without explicit flags, follow the display options the test itself was run with
+    pub fn report_options(&self, summary: &K6Summary) -> ReportOptions {
+        let mut options = self.options.clone();
+        let Some(summary_options) = summary.options.as_ref() else {
+            return options;
+        };
+        if !self.trend_stats_set && !summary_options.summary_trend_stats.is_empty() {
+            options.trend_stats = summary_options
+                .summary_trend_stats
+                .iter()
+                .filter_map(|s| s.parse().ok())
+                .collect();
+        }
+        if !self.time_unit_set {
+            if let Some(unit) = summary_options
+                .summary_time_unit
+                .as_deref()
+                .and_then(|unit| TimeUnit::from_str(unit, true).ok())
+            {
+                options.units.time_unit = unit;
+            }
+        }
+        options
+    }
+
+    /// Renders a summary in `format`, or with the template; `output_path`
+    /// is where the report goes, `-` for stdout
+    pub fn render(
+        &self,
+        format: OutputFormat,
+        summary: &K6Summary,
+        output_path: &Path,
+    ) -> Result<String, Error> {
+        let options = self.report_options(summary);
+        Ok(match (format, &self.template) {
+            (_, Some((name, source))) => render_template(name, source, summary, &options)?,
+            (OutputFormat::Markdown, None) => generate_report(summary, &options),
+            // Colors are only for a terminal, never a file
+            (OutputFormat::Term, None) if output_path != Path::new(STDIO_PATH) => {
+                Terminal { color: false }.render(&build_document(summary, &options))
+            }
+            (format, None) => format.renderer().render(&build_document(summary, &options)),
+        })
+    }
+}
+
+/// Writes a report to its file, or to stdout for `-`
+pub fn write_report(report: &str, output_path: &Path) -> Result<(), Error> {
+    let write_error = |source| Error::Write {
+        path: output_path.to_path_buf(),
+        source,
+    };
+    if output_path == Path::new(STDIO_PATH) {
+        return std::io::stdout()
+            .write_all(report.as_bytes())
+            .map_err(write_error);
+    }
+    std::fs::write(output_path, report).map_err(write_error)?;
+    eprintln!("Report generated: {}", output_path.display());
+    Ok(())
+}
+
+/// Renders the report of a single result file, whatever its format
+pub fn report_file(
+    settings: &ReportSettings,
+    input: &Path,
+    input_format: Option<FileFormat>,
+    format: OutputFormat,
+    output_path: &Path,
+) -> Result<K6Summary, Error> {
+    let summary = settings.load_file(input, input_format)?;
+    let report = settings.render(format, &summary, output_path)?;
+    write_report(&report, output_path)?;
+    Ok(summary)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meta_entry() {
+        assert_eq!(
+            parse_meta_entry("note=a=b"),
+            Ok(("note".to_string(), "a=b".to_string()))
+        );
+        assert!(parse_meta_entry("=value").is_err());
+        assert!(parse_meta_entry("novalue").is_err());
+    }
+
+    #[test]
+    fn test_parse_metric_unit() {
+        assert_eq!(
+            parse_metric_unit("queue_wait=s"),
+            Ok(("queue_wait".to_string(), Unit::Seconds))
+        );
+        assert!(parse_metric_unit("queue_wait").is_err());
+        assert!(parse_metric_unit("queue_wait=parsecs").is_err());
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse_config(
+            r#"
+format = "markdown"
+title = "Checkout Load Test"
+sections = ["summary", "thresholds", "routes"]
+trend_stats = ["avg", "p(99)"]
+exclude = ["http_req_tls*"]
+
+[units]
+time = "ms"
+precision = 1
+metrics = { payload_size = "bytes" }
+
+[thresholds]
+http_req_duration = ["p(95)<500"]
+
+[[routes]]
+heading = "Kafka"
+metrics = ["kafka_*"]
+columns = ["count"]
+
+[meta]
+environment = "staging"
+build = 1234
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::Markdown));
+        assert_eq!(config.title.as_deref(), Some("Checkout Load Test"));
+        assert_eq!(
+            config.sections,
+            Some(vec![Section::Summary, Section::Thresholds, Section::Routes])
+        );
+        assert_eq!(
+            config.trend_stats,
+            vec![TrendStat::Avg, TrendStat::Percentile(99.0)]
+        );
+        assert_eq!(config.units.time, Some(TimeUnit::Ms));
+        assert_eq!(config.units.precision, Some(1));
+        assert_eq!(config.units.metrics["payload_size"], Unit::Bytes);
+        assert_eq!(config.thresholds["http_req_duration"], vec!["p(95)<500"]);
+        assert_eq!(
+            config.routes,
+            vec!["Kafka=kafka_*:count".parse::<MetricRoute>().unwrap()]
+        );
+        let meta: Vec<(String, String)> = config
+            .meta
+            .into_iter()
+            .map(|(key, value)| (key, value.to_string()))
+            .collect();
+        assert_eq!(
+            meta,
+            vec![
+                ("environment".to_string(), "staging".to_string()),
+                ("build".to_string(), "1234".to_string()),
+            ]
+        );
+
+        let empty = parse_config("").unwrap();
+        assert!(empty.sections.is_none() && empty.routes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_config_errors_name_the_key() {
+        let err = parse_config("[units]\nmetrics = { payload = \"furlongs\" }\n").unwrap_err();
+        assert!(
+            err.starts_with("line 2: units.metrics.payload: "),
+            "{}",
+            err
+        );
+        assert!(err.contains("unknown unit 'furlongs'"), "{}", err);
+
+        let err = parse_config("sections = [\"summary\", \"latncy\"]\n").unwrap_err();
+        assert!(
+            err.contains("sections[1]: unknown section 'latncy'"),
+            "{}",
+            err
+        );
+
+        let err = parse_config("[[routes]]\nheading = \"SQL\"\n").unwrap_err();
+        assert!(
+            err.contains("routes[0]: missing field `metrics`"),
+            "{}",
+            err
+        );
+
+        let err = parse_config("titel = \"x\"\n").unwrap_err();
+        assert!(err.contains("unknown field `titel`"), "{}", err);
+
+        let err = parse_config("title = \n").unwrap_err();
+        assert!(err.starts_with("line 1: "), "{}", err);
+    }
+
+    #[test]
+    fn test_find_config_walks_up() {
+        let root = std::env::temp_dir().join(format!("k6r-config-{}", std::process::id()));
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_ne!(find_config(&nested), Some(root.join(CONFIG_FILE_NAME)));
+
+        std::fs::write(root.join(CONFIG_FILE_NAME), "").unwrap();
+        assert_eq!(find_config(&nested), Some(root.join(CONFIG_FILE_NAME)));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// Everything that can go wrong reading inputs or writing reports
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A file could not be read
+    Read {
+        /// The file
+        path: PathBuf,
+        /// Why reading failed
+        source: std::io::Error,
+    },
+    /// A report or export file could not be written
+    Write {
+        /// The file
+        path: PathBuf,
+        /// Why writing failed
+        source: std::io::Error,
+    },
+    /// Reading or decompressing the input stream failed part-way
+    Input(std::io::Error),
+    /// The input is not in any format k6r understands
+    Format(String),
+    /// A handleSummary document failed to deserialize
+    Json(serde_json::Error),
+    /// A JSONL line failed to parse (only fatal in strict mode)
+    Jsonl {
+        /// 1-based line number
+        line: usize,
+        /// What is wrong with the line
+        message: String,
+    },
+    /// A CSV row failed to parse (only fatal in strict mode)
+    Csv {
+        /// 1-based line number
+        line: usize,
+        /// What is wrong with the row
+        message: String,
+    },
+    /// The k6 script's options could not be extracted
+    Script {
+        /// The script
+        path: PathBuf,
+        /// Why the options could not be read
+        message: String,
+    },
+    /// The given inputs cannot be combined into one report
+    Combine(String),
+    /// The requested time window cannot be applied to the points
+    Window(String),
+    /// An option only works on raw points, but only a summary was given
+    NeedsPoints(&'static str),
+    /// The k6r.toml file is malformed or has invalid values
+    Config {
+        /// The config file
+        path: PathBuf,
+        /// What is wrong with it
+        message: String,
+    },
+    /// A report template failed to compile or render
+    Template {
+        /// The template's file name
+        name: String,
+        /// The MiniJinja error, with the line it occurred on
+        message: String,
+    },
+    /// Files could not be watched for changes
+    Watch(String),
+    /// A directory holds no result files
+    NoInputs(PathBuf),
+    /// The report server could not start listening
+    Serve(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "Failed to read '{}': {}", path.display(), source)
+            }
+            Error::Write { path, source } => {
+                write!(f, "Failed to write '{}': {}", path.display(), source)
+            }
+            Error::Input(source) => write!(f, "Failed to read input: {}", source),
+            Error::Format(message) => write!(f, "Unrecognized input format: {}", message),
+            Error::Json(e) => write!(f, "Failed to parse JSON: {}", e),
+            Error::Jsonl { line, message } => {
+                write!(f, "Failed to parse JSONL line {}: {}", line, message)
+            }
+            Error::Csv { line, message } => {
+                write!(f, "Failed to parse CSV line {}: {}", line, message)
+            }
+            Error::Script { path, message } => write!(
+                f,
+                "Failed to parse options in '{}': {}",
+                path.display(),
+                message
+            ),
+            Error::Combine(message) => write!(f, "Cannot combine inputs: {}", message),
+            Error::Window(message) => write!(f, "Invalid time window: {}", message),
+            Error::NeedsPoints(option) => write!(
+                f,
+                "{} need the run's points (--out json or csv); a summary only has whole-run stats",
+                option
+            ),
+            Error::Config { path, message } => {
+                write!(f, "Invalid config '{}': {}", path.display(), message)
+            }
+            Error::Template { name, message } => {
+                write!(f, "Failed to render template '{}': {}", name, message)
+            }
+            Error::Watch(message) => write!(f, "Failed to watch for changes: {}", message),
+            Error::NoInputs(dir) => write!(f, "No result files in '{}'", dir.display()),
+            Error::Serve(message) => write!(f, "Failed to start the server: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } | Error::Write { source, .. } | Error::Input(source) => {
+                Some(source)
+            }
+            Error::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
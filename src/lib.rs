@@ -0,0 +1,36 @@
+//! Convert k6 load test results into reports.
+//!
+//! The pipeline has three stages, one module each:
+//!
+//! - [`parse`] reads k6 output (handleSummary JSON, `--summary-export` JSON,
+//!   `--out json` or `--out csv` points, optionally gzip or zstd compressed)
+//!   into a [`K6Summary`]
+//! - [`analyze`] works on summaries: statistics, thresholds, time windows,
+//!   tag filters and combining a summary with the run's points
+//! - [`render`] turns a summary into a report: Markdown, HTML or plain text
+//!   through a [`render::Renderer`], or a template
+//!
+//! On top of them, [`config`] turns the report flags and `k6r.toml` into
+//! [`config::ReportSettings`], and [`command`] holds the `k6r` subcommands
+//! (report, watch, batch, live and serve) so the binary only parses arguments.
+//!
+//! ```no_run
+//! use k6r::{parse, render};
+//!
+//! let (_, summary) = parse::load_input("results.json".as_ref(), None, &parse::ParseOptions::default())?;
+//! let report = render::generate_report(&summary, &render::ReportOptions::default());
+//! # Ok::<(), k6r::Error>(())
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod analyze;
+pub mod command;
+pub mod config;
+mod error;
+mod model;
+pub mod parse;
+pub mod render;
+
+pub use error::Error;
+pub use model::*;
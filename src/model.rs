@@ -0,0 +1,588 @@
+//! The summary data model shared by every stage.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+// =============================================================================
+// Data Model - handleSummary format
+// =============================================================================
+
+/// A k6 test run's results, as in handleSummary's `data` argument. Point
+/// input fills in the same structure plus the time-based fields.
+#[derive(Debug, Deserialize, Default)]
+#[non_exhaustive]
+pub struct K6Summary {
+    /// Every metric by name, including submetrics such as `http_req_duration{status:200}`
+    #[serde(default)]
+    pub metrics: HashMap<String, Metric>,
+    /// The root group with the checks and nested groups
+    pub root_group: Option<Group>,
+    /// The run state, for the test run duration
+    pub state: Option<State>,
+    /// The options the test was run with
+    pub options: Option<TestOptions>,
+    /// What the script's `setup()` returned
+    pub setup_data: Option<serde_json::Value>,
+    /// Problems found while reading the input, shown at the top of the report
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+    /// Sorted raw samples of latency trends, only available for point input
+    #[serde(skip)]
+    pub samples: HashMap<String, Vec<f64>>,
+    /// Per-endpoint request breakdown, busiest first, only available for point input
+    #[serde(skip)]
+    pub endpoints: Vec<EndpointStats>,
+    /// Fixed-width intervals from the first to the last point, only available for point input
+    #[serde(skip)]
+    pub timeline: Vec<TimelineBucket>,
+    /// Part of the run the points were restricted to, if any
+    #[serde(skip)]
+    pub window: Option<TimeWindow>,
+    /// Tag filters the points were restricted to
+    #[serde(skip)]
+    pub filters: Vec<TagFilter>,
+}
+
+/// One metric's aggregate values, keyed by stat (`count`, `rate`, `avg`, `p(95)`, ...)
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct Metric {
+    /// Decides which stats `values` holds
+    #[serde(rename = "type")]
+    pub metric_type: MetricType,
+    /// `time`, `data` or `default`: what the values measure
+    #[serde(default)]
+    pub contains: String,
+    /// Aggregate values by stat key
+    #[serde(default)]
+    pub values: HashMap<String, f64>,
+    /// Threshold outcomes by expression
+    #[serde(default)]
+    pub thresholds: HashMap<String, Threshold>,
+}
+
+impl Metric {
+    /// A metric without values or thresholds
+    pub fn new(metric_type: MetricType, contains: impl Into<String>) -> Self {
+        Metric {
+            metric_type,
+            contains: contains.into(),
+            values: HashMap::new(),
+            thresholds: HashMap::new(),
+        }
+    }
+}
+
+/// k6 metric types, which decide the stats a metric has
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum MetricType {
+    /// A running total, with `count` and `rate`
+    Counter,
+    /// A share of non-zero samples, with `rate`, `passes` and `fails`
+    Rate,
+    /// The latest sample, with `value`, `min` and `max`
+    Gauge,
+    /// A distribution, with the configured trend stats
+    #[default]
+    Trend,
+}
+
+/// Outcome of one threshold expression
+#[derive(Debug, Deserialize, Clone)]
+#[non_exhaustive]
+pub struct Threshold {
+    /// Whether the threshold passed
+    pub ok: bool,
+    /// Set when k6r could not evaluate the expression (e.g. missing data)
+    #[serde(skip)]
+    pub unknown: bool,
+}
+
+impl Threshold {
+    /// An evaluated threshold
+    pub fn new(ok: bool) -> Self {
+        Threshold { ok, unknown: false }
+    }
+}
+
+/// A k6 `group()` with its checks and nested groups
+#[derive(Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Group {
+    /// Empty for the root group
+    pub name: String,
+    /// Nested groups, in k6's order
+    #[serde(default, deserialize_with = "deserialize_entries")]
+    pub groups: Vec<Group>,
+    /// Checks made directly in this group
+    #[serde(default, deserialize_with = "deserialize_entries")]
+    pub checks: Vec<Check>,
+}
+
+/// Passes and fails of one `check()`
+#[derive(Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Check {
+    /// The name given to `check()`
+    pub name: String,
+    /// Times the check held
+    pub passes: u64,
+    /// Times the check failed
+    pub fails: u64,
+}
+
+/// Run state; only the duration is used
+#[derive(Debug, Deserialize)]
+#[non_exhaustive]
+pub struct State {
+    /// Wall-clock duration of the run, setup and teardown included
+    #[serde(rename = "testRunDurationMs")]
+    pub test_run_duration_ms: f64,
+}
+
+/// The test's k6 options, from the summary or the script
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct TestOptions {
+    /// Trend stats the summary was run with, e.g. `p(99)`
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub summary_trend_stats: Vec<String>,
+    /// Fixed time unit of the summary, if any
+    pub summary_time_unit: Option<String>,
+    /// The `vus` shortcut
+    pub vus: Option<u64>,
+    /// The `iterations` shortcut
+    pub iterations: Option<u64>,
+    /// The `duration` shortcut
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub duration: Option<String>,
+    /// The `stages` shortcut
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub stages: Vec<Stage>,
+    /// Scenarios by name
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub scenarios: BTreeMap<String, Scenario>,
+    /// Threshold definitions by metric
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub thresholds: BTreeMap<String, Vec<ThresholdDef>>,
+}
+
+/// One entry of `options.scenarios`
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct Scenario {
+    /// e.g. `ramping-vus` or `constant-arrival-rate`
+    #[serde(default)]
+    pub executor: String,
+    /// VUs of the VU-based executors
+    pub vus: Option<u64>,
+    /// Iterations of the iteration-based executors
+    pub iterations: Option<u64>,
+    /// How long the scenario runs
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub duration: Option<String>,
+    /// Iterations per `time_unit` of the arrival-rate executors
+    pub rate: Option<f64>,
+    /// Period of `rate`
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub time_unit: Option<String>,
+    /// VUs the arrival-rate executors start with
+    #[serde(rename = "preAllocatedVUs")]
+    pub pre_allocated_vus: Option<u64>,
+    /// VUs the arrival-rate executors may grow to
+    #[serde(rename = "maxVUs")]
+    pub max_vus: Option<u64>,
+    /// VUs `ramping-vus` starts with
+    #[serde(rename = "startVUs")]
+    pub start_vus: Option<u64>,
+    /// Rate `ramping-arrival-rate` starts with
+    pub start_rate: Option<f64>,
+    /// Offset from the start of the test
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub start_time: Option<String>,
+    /// The exported function the scenario runs, `default` when unset
+    pub exec: Option<String>,
+    /// Ramping stages
+    #[serde(default, deserialize_with = "deserialize_nullable")]
+    pub stages: Vec<Stage>,
+}
+
+/// A ramping stage: reach `target` over `duration`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Stage {
+    /// How long the ramp takes
+    #[serde(deserialize_with = "deserialize_stage_duration")]
+    pub duration: String,
+    /// VUs or rate at the end of the stage
+    #[serde(default)]
+    pub target: f64,
+}
+
+/// A threshold as written in k6 options: either a bare expression or an object
+/// with `abortOnFail` settings.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum ThresholdDef {
+    /// `"p(95)<500"`
+    Expression(String),
+    /// `{ threshold: "p(95)<500", abortOnFail: true }`
+    Object {
+        /// The expression
+        threshold: String,
+        /// Stop the test as soon as the threshold fails
+        #[serde(default, rename = "abortOnFail")]
+        abort_on_fail: bool,
+    },
+}
+
+impl ThresholdDef {
+    /// The threshold expression, e.g. `p(95)<500`
+    pub fn expression(&self) -> &str {
+        match self {
+            ThresholdDef::Expression(expr) => expr,
+            ThresholdDef::Object { threshold, .. } => threshold,
+        }
+    }
+}
+
+/// Reads `null` as the empty value; k6 writes unset lists as `null`.
+fn deserialize_nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// k6 durations are usually strings ("30s") but plain numbers mean milliseconds.
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Some(s),
+        serde_json::Value::Number(n) => n.as_f64().map(|ms| format!("{}ms", ms)),
+        _ => None,
+    })
+}
+
+fn deserialize_stage_duration<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_duration(deserializer).map(Option::unwrap_or_default)
+}
+
+/// Accepts a list, or a map keyed by name as `--summary-export` writes groups
+/// and checks. Map entries come out in key order, as k6 sorts them.
+fn deserialize_entries<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entries<T> {
+        List(Vec<T>),
+        Map(BTreeMap<String, T>),
+    }
+
+    Ok(match Entries::deserialize(deserializer)? {
+        Entries::List(list) => list,
+        Entries::Map(map) => map.into_values().collect(),
+    })
+}
+
+/// Implements `Deserialize` for types that are written as strings in the
+/// config file and already parse from command-line values
+macro_rules! deserialize_from_str {
+    ($($ty:ty),*) => {$(
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+deserialize_from_str!(TrendStat, Unit, Section);
+
+/// Request statistics for one `method` + `name` pair of `http_req_duration`
+/// points, used for the per-endpoint breakdown
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct EndpointStats {
+    /// HTTP method, empty when untagged
+    pub method: String,
+    /// The `name` tag, or the URL without one
+    pub name: String,
+    /// Number of requests
+    pub requests: usize,
+    /// Requests tagged `expected_response=false`
+    pub failed: usize,
+    /// Mean request duration in milliseconds
+    pub avg: f64,
+    /// 95th percentile request duration in milliseconds
+    pub p95: f64,
+    /// Requests by status code
+    pub statuses: BTreeMap<String, usize>,
+}
+
+/// One interval of the run's timeline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TimelineBucket {
+    /// Offset from the first point
+    pub start_ms: f64,
+    /// Width of the interval
+    pub duration_ms: f64,
+    /// Highest `vus` sample in the interval
+    pub vus: Option<f64>,
+    /// `http_reqs` in the interval
+    pub requests: f64,
+    /// Mean `http_req_duration` in the interval
+    pub avg: Option<f64>,
+    /// 95th percentile `http_req_duration` in the interval
+    pub p95: Option<f64>,
+    /// Share of `http_req_failed` samples that were failures
+    pub error_rate: Option<f64>,
+}
+
+/// The part of the run the points were aggregated over, as offsets from the
+/// first point
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TimeWindow {
+    /// Start of the window
+    pub start_ms: f64,
+    /// End of the window
+    pub end_ms: f64,
+    /// Span of the whole run
+    pub run_ms: f64,
+    /// Peak VUs when the window is the detected steady state
+    pub steady_vus: Option<f64>,
+}
+
+/// A `--tag key=value` or `key!=value` filter; the value is a glob pattern
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct TagFilter {
+    /// Tag name
+    pub key: String,
+    /// Glob pattern the tag's value must match
+    pub pattern: String,
+    /// Keep the points that do not match instead (`!=`)
+    pub negate: bool,
+}
+
+impl FromStr for TagFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, pattern, negate) = match s.split_once("!=") {
+            Some((key, pattern)) => (key, pattern, true),
+            None => match s.split_once('=') {
+                Some((key, pattern)) => (key, pattern, false),
+                None => return Err(format!("expected KEY=VALUE or KEY!=VALUE, got '{}'", s)),
+            },
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("missing tag name in '{}'", s));
+        }
+        Ok(TagFilter {
+            key: key.to_string(),
+            pattern: pattern.trim().to_string(),
+            negate,
+        })
+    }
+}
+
+impl std::fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = if self.negate { "!=" } else { "=" };
+        write!(f, "{}{}{}", self.key, op, self.pattern)
+    }
+}
+
+// =============================================================================
+// Report Settings
+// =============================================================================
+
+/// A trend statistic as named by k6's `summaryTrendStats` option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum TrendStat {
+    /// `avg`
+    Avg,
+    /// `min`
+    Min,
+    /// `med`
+    Med,
+    /// `max`
+    Max,
+    /// `count`
+    Count,
+    /// `p(N)`, N being a percentage
+    Percentile(f64),
+}
+
+impl TrendStat {
+    /// Key used in metric values, e.g. `p(99.9)`
+    pub(crate) fn key(&self) -> String {
+        match self {
+            TrendStat::Avg => "avg".to_string(),
+            TrendStat::Min => "min".to_string(),
+            TrendStat::Med => "med".to_string(),
+            TrendStat::Max => "max".to_string(),
+            TrendStat::Count => "count".to_string(),
+            TrendStat::Percentile(p) => format!("p({})", p),
+        }
+    }
+}
+
+impl FromStr for TrendStat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "avg" => Ok(TrendStat::Avg),
+            "min" => Ok(TrendStat::Min),
+            "med" => Ok(TrendStat::Med),
+            "max" => Ok(TrendStat::Max),
+            "count" => Ok(TrendStat::Count),
+            other => {
+                let p = other
+                    .strip_prefix("p(")
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .and_then(|n| n.trim().parse::<f64>().ok())
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid trend stat '{}' (expected avg, min, med, max, count or p(N))",
+                            other
+                        )
+                    })?;
+                if !(0.0..=100.0).contains(&p) {
+                    return Err(format!("percentile out of range in '{}'", other));
+                }
+                Ok(TrendStat::Percentile(p))
+            }
+        }
+    }
+}
+
+/// Unit of a metric's raw values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Unit {
+    /// Milliseconds, as k6 records `contains: "time"` metrics
+    Milliseconds,
+    /// Seconds
+    Seconds,
+    /// Bytes, shown in binary multiples
+    Bytes,
+    /// A plain number
+    Plain,
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ms" | "time" => Ok(Unit::Milliseconds),
+            "s" => Ok(Unit::Seconds),
+            "bytes" | "b" | "data" => Ok(Unit::Bytes),
+            "count" | "none" | "default" => Ok(Unit::Plain),
+            other => Err(format!(
+                "unknown unit '{}' (expected ms, s, bytes or count)",
+                other
+            )),
+        }
+    }
+}
+
+/// Report sections, addressable by name for ordering and selection
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Section {
+    /// Requests, failure rate and response times
+    Summary,
+    /// Scenarios and setup data from the test options
+    Configuration,
+    /// Every threshold with its outcome
+    Thresholds,
+    /// The `http_req_*` trends
+    Http,
+    /// Requests per method and name (point input only)
+    Endpoints,
+    /// Per-interval VUs, requests, latency and errors (point input only)
+    Timeline,
+    /// Latency histogram and percentile spectrum (point input only)
+    Latency,
+    /// Passes and fails of every check
+    Checks,
+    /// Every `--route` section, in route order
+    Routes,
+    /// Every other metric by type
+    Metrics,
+}
+
+impl Section {
+    /// All sections in their default order
+    pub const ALL: [Section; 10] = [
+        Section::Summary,
+        Section::Configuration,
+        Section::Thresholds,
+        Section::Http,
+        Section::Endpoints,
+        Section::Timeline,
+        Section::Latency,
+        Section::Checks,
+        Section::Routes,
+        Section::Metrics,
+    ];
+
+    /// The name used in `--sections`, the config file and templates
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Summary => "summary",
+            Section::Configuration => "configuration",
+            Section::Thresholds => "thresholds",
+            Section::Http => "http",
+            Section::Endpoints => "endpoints",
+            Section::Timeline => "timeline",
+            Section::Latency => "latency",
+            Section::Checks => "checks",
+            Section::Routes => "routes",
+            Section::Metrics => "metrics",
+        }
+    }
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Section::ALL
+            .into_iter()
+            .find(|section| section.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Section::ALL.iter().map(|s| s.name()).collect();
+                format!(
+                    "unknown section '{}' (expected one of {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
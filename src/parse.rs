@@ -0,0 +1,3001 @@
+//! Reading k6 output into a [`K6Summary`]: format detection, decompression,
+//! the summary, summary export, JSONL and CSV parsers, and k6 script options.
+
+use crate::analyze::{
+    calculate_stats, default_trend_stats, evaluate_threshold, is_latency_metric,
+    matches_tag_filters, percentile, resolve_window, sample_stat, RollingStats, WindowOptions,
+};
+use crate::{
+    EndpointStats, Error, Group, K6Summary, Metric, MetricType, State, TagFilter, TestOptions,
+    Threshold, TimelineBucket, TrendStat,
+};
+use clap::ValueEnum;
+use flate2::read::MultiGzDecoder;
+use ruzstd::decoding::{FrameDecoder, StreamingDecoder};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+// =============================================================================
+// Data Model - legacy --summary-export format
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+struct SummaryExport {
+    #[serde(default)]
+    pub metrics: HashMap<String, ExportMetric>,
+    pub root_group: Option<Group>,
+}
+
+/// Stats sit directly on the metric, next to an optional threshold map
+#[derive(Debug, Deserialize)]
+struct ExportMetric {
+    /// Expression -> whether the threshold *failed*
+    #[serde(default)]
+    pub thresholds: HashMap<String, bool>,
+    #[serde(flatten)]
+    pub values: HashMap<String, f64>,
+}
+
+// =============================================================================
+// Data Model - JSONL format (--out json)
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+struct JsonlLine {
+    #[serde(rename = "type")]
+    pub line_type: String,
+    pub metric: String,
+    pub data: JsonlData,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonlData {
+    // For Metric type
+    #[serde(rename = "type")]
+    pub metric_type: Option<String>,
+    pub contains: Option<String>,
+    #[serde(default)]
+    pub thresholds: Vec<String>,
+
+    // For Point type
+    pub time: Option<String>,
+    pub value: Option<f64>,
+    #[serde(default)]
+    pub tags: Option<HashMap<String, serde_json::Value>>,
+}
+
+// =============================================================================
+// Point Aggregation
+// =============================================================================
+
+/// How many malformed lines are reported individually before only counting
+const MAX_REPORTED_LINE_ERRORS: usize = 5;
+
+/// Why a point whose value is NaN or infinite is skipped; no statistic of it
+/// would mean anything
+const NON_FINITE_VALUE: &str = "metric value is not a finite number";
+const NO_JSONL_LINES: &str = "no JSONL lines found";
+/// Why a stream with rolling stats cannot take `--from`, `--to` or
+/// `--auto-steady-state`
+pub(crate) const ROLLING_WINDOW: &str =
+    "live mode keeps running stats rather than the points a time window needs";
+
+/// How points are aggregated into a summary
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Stats computed for trend metrics
+    pub trend_stats: Vec<TrendStat>,
+    /// Fail on the first malformed line instead of skipping it
+    pub strict: bool,
+    /// Threshold expressions to evaluate per metric, in addition to those
+    /// declared in the JSONL metric definitions
+    pub thresholds: BTreeMap<String, Vec<String>>,
+    /// Part of the run to aggregate
+    pub window: WindowOptions,
+    /// Only aggregate points whose tags match
+    pub tag_filters: Vec<TagFilter>,
+    /// k6's own run duration, such as a handleSummary's `testRunDurationMs`.
+    /// Without it the duration and counter rates are derived from the points,
+    /// from the first to the last, which leaves out setup and teardown.
+    pub run_duration_ms: Option<f64>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            trend_stats: default_trend_stats(),
+            strict: false,
+            thresholds: BTreeMap::new(),
+            window: WindowOptions::default(),
+            tag_filters: Vec::new(),
+            run_duration_ms: None,
+        }
+    }
+}
+
+/// Upper bound on timeline rows; the interval is rounded up to a readable step
+const MAX_TIMELINE_BUCKETS: usize = 20;
+
+const TIMELINE_STEPS_MS: [f64; 13] = [
+    1_000.0,
+    2_000.0,
+    5_000.0,
+    10_000.0,
+    15_000.0,
+    30_000.0,
+    60_000.0,
+    120_000.0,
+    300_000.0,
+    600_000.0,
+    900_000.0,
+    1_800_000.0,
+    3_600_000.0,
+];
+
+/// Tag lookup over the different shapes points carry their tags in
+pub(crate) trait PointTags {
+    fn tag(&self, key: &str) -> Option<&str>;
+    fn is_empty(&self) -> bool;
+}
+
+impl PointTags for HashMap<String, serde_json::Value> {
+    fn tag(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|v| v.as_str())
+    }
+
+    fn is_empty(&self) -> bool {
+        HashMap::is_empty(self)
+    }
+}
+
+impl PointTags for HashMap<String, String> {
+    fn tag(&self, key: &str) -> Option<&str> {
+        self.get(key).map(String::as_str)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashMap::is_empty(self)
+    }
+}
+
+struct MetricCollector {
+    metric_type: MetricType,
+    contains: String,
+    /// (time, value) samples; the time window is applied once all are read
+    points: Vec<(f64, f64)>,
+    thresholds: Vec<String>,
+}
+
+/// Timed samples of the metrics the timeline is built from
+#[derive(Default)]
+struct SeriesCollector {
+    vus: Vec<(f64, f64)>,
+    requests: Vec<(f64, f64)>,
+    durations: Vec<(f64, f64)>,
+    failed: Vec<(f64, f64)>,
+}
+
+struct EndpointSample {
+    time: f64,
+    duration: f64,
+    failed: bool,
+    status: Option<String>,
+}
+
+/// Type and `contains` of k6's built-in metrics, for inputs that carry no
+/// metric definitions. Custom metrics fall back to a plain trend.
+fn builtin_metric(name: &str) -> (MetricType, &'static str) {
+    match name {
+        "vus" | "vus_max" => (MetricType::Gauge, "default"),
+        "http_reqs"
+        | "iterations"
+        | "dropped_iterations"
+        | "ws_msgs_sent"
+        | "ws_msgs_received"
+        | "ws_sessions"
+        | "grpc_streams"
+        | "grpc_streams_msgs_sent"
+        | "grpc_streams_msgs_received" => (MetricType::Counter, "default"),
+        "data_sent" | "data_received" => (MetricType::Counter, "data"),
+        "checks" | "http_req_failed" => (MetricType::Rate, "default"),
+        "http_req_duration"
+        | "http_req_blocked"
+        | "http_req_connecting"
+        | "http_req_tls_handshaking"
+        | "http_req_sending"
+        | "http_req_waiting"
+        | "http_req_receiving"
+        | "iteration_duration"
+        | "group_duration"
+        | "ws_connecting"
+        | "ws_session_duration"
+        | "ws_ping"
+        | "grpc_req_duration" => (MetricType::Trend, "time"),
+        _ => (MetricType::Trend, ""),
+    }
+}
+
+/// A metric's points with certain tags, such as `http_req_duration{status:200}`,
+/// which k6 reports as a metric of its own
+struct Submetric {
+    name: String,
+    parent: String,
+    tags: Vec<(String, String)>,
+}
+
+impl Submetric {
+    /// Parses `metric{tag:value,...}`; `None` for a plain metric name
+    fn parse(name: &str) -> Option<Submetric> {
+        let (parent, selector) = name.split_once('{')?;
+        let selector = selector.trim_end().strip_suffix('}')?;
+        let tags = selector
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                // Values such as `::setup` groups or URLs keep their colons
+                let (key, value) = pair.split_once(':').unwrap_or((pair, ""));
+                let unquote = |text: &str| text.trim().trim_matches(['"', '\'']).to_string();
+                (unquote(key), unquote(value))
+            })
+            .collect();
+        Some(Submetric {
+            name: name.to_string(),
+            parent: parent.trim().to_string(),
+            tags,
+        })
+    }
+
+    fn matches(&self, tags: &impl PointTags) -> bool {
+        self.tags
+            .iter()
+            .all(|(key, value)| tags.tag(key) == Some(value.as_str()))
+    }
+}
+
+/// Builds a `K6Summary` from raw metric points, the way k6's metric sinks do.
+/// Shared by the line-based result formats.
+struct Aggregator<'a> {
+    options: &'a ParseOptions,
+    /// Builds the format-specific error for a malformed line
+    line_error: fn(usize, String) -> Error,
+    collectors: HashMap<String, MetricCollector>,
+    /// Submetrics that thresholds are set on, collected besides their metric
+    submetrics: Vec<Submetric>,
+    endpoints: HashMap<(String, String), Vec<EndpointSample>>,
+    series: SeriesCollector,
+    first_time: Option<f64>,
+    last_time: Option<f64>,
+    parsed_lines: usize,
+    skipped: Vec<(usize, String)>,
+}
+
+impl<'a> Aggregator<'a> {
+    fn new(options: &'a ParseOptions, line_error: fn(usize, String) -> Error) -> Self {
+        Aggregator {
+            options,
+            line_error,
+            collectors: HashMap::new(),
+            submetrics: options
+                .thresholds
+                .keys()
+                .filter_map(|name| Submetric::parse(name))
+                .collect(),
+            endpoints: HashMap::new(),
+            series: SeriesCollector::default(),
+            first_time: None,
+            last_time: None,
+            parsed_lines: 0,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Records a malformed line, or fails on it in strict mode
+    fn skip(&mut self, line: usize, message: String) -> Result<(), Error> {
+        if self.options.strict {
+            return Err((self.line_error)(line, message));
+        }
+        self.skipped.push((line, message));
+        Ok(())
+    }
+
+    fn declare(
+        &mut self,
+        name: &str,
+        metric_type: MetricType,
+        contains: String,
+        thresholds: Vec<String>,
+    ) {
+        if !self.submetrics.iter().any(|s| s.name == name) {
+            self.submetrics.extend(Submetric::parse(name));
+        }
+        self.collectors
+            .entry(name.to_string())
+            .or_insert(MetricCollector {
+                metric_type,
+                contains,
+                points: Vec::new(),
+                thresholds,
+            });
+    }
+
+    /// Points are not strictly ordered, so this tracks the full time range. The
+    /// run's span includes filtered-out points, as k6's submetric rates do.
+    fn track_time(&mut self, time: Option<f64>) {
+        if let Some(time) = time {
+            self.first_time = Some(self.first_time.map_or(time, |t| t.min(time)));
+            self.last_time = Some(self.last_time.map_or(time, |t| t.max(time)));
+        }
+    }
+
+    fn add_point(&mut self, name: &str, time: Option<f64>, value: f64, tags: &impl PointTags) {
+        self.track_time(time);
+        if !matches_tag_filters(&self.options.tag_filters, tags) {
+            return;
+        }
+
+        if let Some(time) = time {
+            let series = match name {
+                "vus" => Some(&mut self.series.vus),
+                "http_reqs" => Some(&mut self.series.requests),
+                "http_req_duration" => Some(&mut self.series.durations),
+                "http_req_failed" => Some(&mut self.series.failed),
+                _ => None,
+            };
+            if let Some(series) = series {
+                series.push((time, value));
+            }
+        }
+        let time = time.or(self.last_time).unwrap_or(0.0);
+
+        // Tagged points still belong to the metric itself; k6 only
+        // reports them separately as submetrics on top of that
+        let collector = self.collectors.entry(name.to_string()).or_insert_with(|| {
+            let (metric_type, contains) = builtin_metric(name);
+            MetricCollector {
+                metric_type,
+                contains: contains.to_string(),
+                points: Vec::new(),
+                thresholds: Vec::new(),
+            }
+        });
+        collector.points.push((time, value));
+        let (metric_type, contains) = (collector.metric_type, collector.contains.clone());
+        for submetric in &self.submetrics {
+            if submetric.parent == name && submetric.matches(tags) {
+                self.collectors
+                    .entry(submetric.name.clone())
+                    .or_insert_with(|| MetricCollector {
+                        metric_type,
+                        contains: contains.clone(),
+                        points: Vec::new(),
+                        thresholds: Vec::new(),
+                    })
+                    .points
+                    .push((time, value));
+            }
+        }
+
+        if name == "http_req_duration" {
+            let method = tags.tag("method").unwrap_or_default().to_string();
+            let endpoint = tags.tag("name").or_else(|| tags.tag("url"));
+            let endpoint = endpoint.unwrap_or_default().to_string();
+            self.endpoints
+                .entry((method, endpoint))
+                .or_default()
+                .push(EndpointSample {
+                    time,
+                    duration: value,
+                    failed: tags.tag("expected_response") == Some("false"),
+                    status: tags.tag("status").map(str::to_string),
+                });
+        }
+    }
+
+    /// Type of a metric that was declared or has points, else of the built-in
+    fn metric_type(&self, name: &str) -> MetricType {
+        self.collectors
+            .get(name)
+            .map_or_else(|| builtin_metric(name).0, |c| c.metric_type)
+    }
+
+    /// Type and `contains` of a metric, taken from the parent for a submetric
+    /// that has no points
+    fn metric_kind(&self, name: &str) -> (MetricType, String) {
+        let name = match name.split_once('{') {
+            Some((parent, _)) if !self.collectors.contains_key(name) => parent.trim(),
+            _ => name,
+        };
+        match self.collectors.get(name) {
+            Some(collector) => (collector.metric_type, collector.contains.clone()),
+            None => {
+                let (metric_type, contains) = builtin_metric(name);
+                (metric_type, contains.to_string())
+            }
+        }
+    }
+
+    /// The summary of points that went into `rolling` only, which keeps
+    /// running stats instead of the points: no samples, endpoints or
+    /// timeline, and percentiles from its histograms
+    fn finish_running(self, rolling: &RollingStats, empty: &str) -> Result<K6Summary, Error> {
+        let warnings = self.line_warnings(empty)?;
+        let options = self.options;
+        if options.window.is_set() {
+            return Err(Error::Window(ROLLING_WINDOW.to_string()));
+        }
+
+        let mut metrics: HashMap<String, Metric> = HashMap::new();
+        for (name, metric_type, values) in rolling.metric_values(&options.trend_stats) {
+            let (_, contains) = self.metric_kind(name);
+            let metric = Metric {
+                metric_type,
+                contains,
+                values,
+                thresholds: HashMap::new(),
+            };
+            metrics.insert(name.to_string(), metric);
+        }
+        for (name, expr, threshold) in rolling.thresholds() {
+            let metric = metrics.entry(name.to_string()).or_insert_with(|| {
+                let (metric_type, contains) = self.metric_kind(name);
+                Metric {
+                    metric_type,
+                    contains,
+                    values: HashMap::new(),
+                    thresholds: HashMap::new(),
+                }
+            });
+            metric.thresholds.insert(expr.to_string(), threshold);
+        }
+
+        let run = self.first_time.zip(self.last_time);
+        let duration_ms = options
+            .run_duration_ms
+            .or(run.map(|(first, last)| last - first));
+        Ok(K6Summary {
+            metrics,
+            state: duration_ms.map(|ms| State {
+                test_run_duration_ms: ms,
+            }),
+            warnings,
+            filters: options.tag_filters.clone(),
+            ..K6Summary::default()
+        })
+    }
+
+    /// Warnings about skipped lines; an error when not a single line could be
+    /// parsed, which `empty` describes
+    fn line_warnings(&self, empty: &str) -> Result<Vec<String>, Error> {
+        if self.parsed_lines == 0 {
+            return Err(match self.skipped.first() {
+                Some((line, message)) => (self.line_error)(*line, message.clone()),
+                None => Error::Format(empty.to_string()),
+            });
+        }
+
+        let mut warnings = Vec::new();
+        if !self.skipped.is_empty() {
+            warnings.push(format!(
+                "Skipped {} malformed line(s) of {}; statistics may be incomplete",
+                self.skipped.len(),
+                self.parsed_lines + self.skipped.len()
+            ));
+            for (line, message) in self.skipped.iter().take(MAX_REPORTED_LINE_ERRORS) {
+                warnings.push(format!("line {}: {}", line, message));
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// `empty` describes the input when not a single line could be parsed
+    fn finish(self, empty: &str) -> Result<K6Summary, Error> {
+        let options = self.options;
+        let warnings = self.line_warnings(empty)?;
+
+        let run = self.first_time.zip(self.last_time);
+        let window = match run {
+            Some((first, last)) => resolve_window(&options.window, first, last, &self.series.vus)?,
+            None if options.window.is_set() => {
+                return Err(Error::Window("the points carry no timestamps".to_string()))
+            }
+            None => None,
+        };
+        // Untimed points only occur without a window, so they are always kept
+        let (start, end) = match (&window, run) {
+            (Some(window), Some((first, _))) => (first + window.start_ms, first + window.end_ms),
+            _ => (f64::NEG_INFINITY, f64::INFINITY),
+        };
+        let in_window = |time: f64| time >= start && time <= end;
+        let duration_ms = match (&window, run) {
+            (Some(window), _) => Some(window.end_ms - window.start_ms),
+            (None, run) => options
+                .run_duration_ms
+                .or(run.map(|(first, last)| last - first)),
+        };
+
+        // Submetrics without a matching point still have their thresholds
+        // evaluated, on no samples, as k6 does
+        let mut collectors = self.collectors;
+        for submetric in &self.submetrics {
+            if collectors.contains_key(&submetric.name) {
+                continue;
+            }
+            let (metric_type, contains) = match collectors.get(&submetric.parent) {
+                Some(parent) => (parent.metric_type, parent.contains.clone()),
+                None => {
+                    let (metric_type, contains) = builtin_metric(&submetric.parent);
+                    (metric_type, contains.to_string())
+                }
+            };
+            let collector = MetricCollector {
+                metric_type,
+                contains,
+                points: Vec::new(),
+                thresholds: Vec::new(),
+            };
+            collectors.insert(submetric.name.clone(), collector);
+        }
+
+        // Convert collectors to metrics
+        let mut metrics: HashMap<String, Metric> = HashMap::new();
+        let mut samples: HashMap<String, Vec<f64>> = HashMap::new();
+
+        for (name, collector) in collectors {
+            let points: Vec<(f64, f64)> = collector
+                .points
+                .into_iter()
+                .filter(|(time, _)| in_window(*time))
+                .collect();
+            let sample_values: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+
+            let mut values = calculate_stats(
+                &sample_values,
+                collector.metric_type,
+                &options.trend_stats,
+                duration_ms,
+            );
+            // k6 reports a gauge's most recent sample
+            let latest =
+                points.iter().fold(
+                    None,
+                    |latest: Option<(f64, f64)>, &(time, value)| match latest {
+                        Some((latest_time, _)) if time < latest_time => latest,
+                        _ => Some((time, value)),
+                    },
+                );
+            if let (MetricType::Gauge, Some((_, latest))) = (collector.metric_type, latest) {
+                values.insert("value".to_string(), latest);
+            }
+
+            // Submetrics are only there for their thresholds
+            if is_latency_metric(&name, collector.metric_type, &collector.contains)
+                && !name.contains('{')
+                && !sample_values.is_empty()
+            {
+                let mut sorted = sample_values.clone();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                samples.insert(name.clone(), sorted);
+            }
+
+            let mut expressions = collector.thresholds.clone();
+            for expr in options.thresholds.get(&name).into_iter().flatten() {
+                if !expressions.contains(expr) {
+                    expressions.push(expr.clone());
+                }
+            }
+
+            let thresholds: HashMap<String, Threshold> = expressions
+                .into_iter()
+                .map(|expr| {
+                    let result = evaluate_threshold(&expr, |stat| {
+                        values
+                            .get(stat)
+                            .copied()
+                            .or_else(|| sample_stat(&sample_values, collector.metric_type, stat))
+                    });
+                    let threshold = Threshold {
+                        ok: result.unwrap_or(false),
+                        unknown: result.is_none(),
+                    };
+                    (expr, threshold)
+                })
+                .collect();
+
+            metrics.insert(
+                name,
+                Metric {
+                    metric_type: collector.metric_type,
+                    contains: collector.contains,
+                    values,
+                    thresholds,
+                },
+            );
+        }
+
+        let mut endpoints: Vec<EndpointStats> = self
+            .endpoints
+            .into_iter()
+            .filter_map(|((method, name), endpoint_samples)| {
+                let mut durations = Vec::new();
+                let mut failed = 0;
+                let mut statuses: BTreeMap<String, usize> = BTreeMap::new();
+                for sample in endpoint_samples.into_iter().filter(|s| in_window(s.time)) {
+                    durations.push(sample.duration);
+                    if sample.failed {
+                        failed += 1;
+                    }
+                    if let Some(status) = sample.status {
+                        *statuses.entry(status).or_default() += 1;
+                    }
+                }
+                if durations.is_empty() {
+                    return None;
+                }
+                durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let requests = durations.len();
+                Some(EndpointStats {
+                    method,
+                    name,
+                    requests,
+                    failed,
+                    avg: durations.iter().sum::<f64>() / requests as f64,
+                    p95: percentile(&durations, 95.0),
+                    statuses,
+                })
+            })
+            .collect();
+        endpoints.sort_by(|a, b| {
+            b.requests
+                .cmp(&a.requests)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.method.cmp(&b.method))
+        });
+
+        let timeline = match run {
+            Some((first, last)) if last > first => {
+                let (start, end) = (start.max(first), end.min(last));
+                build_timeline(&self.series, first, start, end)
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(K6Summary {
+            metrics,
+            root_group: None,
+            state: duration_ms.map(|ms| State {
+                test_run_duration_ms: ms,
+            }),
+            options: None,
+            setup_data: None,
+            warnings,
+            samples,
+            endpoints,
+            timeline,
+            window,
+            filters: options.tag_filters.clone(),
+        })
+    }
+}
+
+/// Buckets the series between `start` and `end`, labelled by their offset from
+/// the run's first point at `origin`
+fn build_timeline(
+    series: &SeriesCollector,
+    origin: f64,
+    start: f64,
+    end: f64,
+) -> Vec<TimelineBucket> {
+    let span = end - start;
+    if span <= 0.0 {
+        return Vec::new();
+    }
+    let step = TIMELINE_STEPS_MS
+        .iter()
+        .copied()
+        .find(|step| span / step <= MAX_TIMELINE_BUCKETS as f64)
+        .unwrap_or(span / MAX_TIMELINE_BUCKETS as f64);
+    let count = ((span / step).ceil() as usize).max(1);
+    let bucket_of = |time: f64| {
+        (time >= start && time <= end).then(|| (((time - start) / step) as usize).min(count - 1))
+    };
+
+    let mut vus: Vec<Option<f64>> = vec![None; count];
+    let mut requests = vec![0.0; count];
+    let mut durations: Vec<Vec<f64>> = vec![Vec::new(); count];
+    let mut failed = vec![(0.0, 0.0); count];
+
+    for &(time, value) in &series.vus {
+        if let Some(idx) = bucket_of(time) {
+            vus[idx] = Some(vus[idx].map_or(value, |max: f64| max.max(value)));
+        }
+    }
+    for &(time, value) in &series.requests {
+        if let Some(idx) = bucket_of(time) {
+            requests[idx] += value;
+        }
+    }
+    for &(time, value) in &series.durations {
+        if let Some(idx) = bucket_of(time) {
+            durations[idx].push(value);
+        }
+    }
+    for &(time, value) in &series.failed {
+        if let Some(idx) = bucket_of(time) {
+            let (fails, total) = &mut failed[idx];
+            if value != 0.0 {
+                *fails += 1.0;
+            }
+            *total += 1.0;
+        }
+    }
+
+    (0..count)
+        .map(|idx| {
+            let offset = idx as f64 * step;
+            let sorted = &mut durations[idx];
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let (fails, total) = failed[idx];
+            TimelineBucket {
+                start_ms: start - origin + offset,
+                duration_ms: step.min(span - offset),
+                vus: vus[idx],
+                requests: requests[idx],
+                avg: (!sorted.is_empty()).then(|| sorted.iter().sum::<f64>() / sorted.len() as f64),
+                p95: (!sorted.is_empty()).then(|| percentile(sorted, 95.0)),
+                error_rate: (total > 0.0).then(|| fails / total),
+            }
+        })
+        .collect()
+}
+
+/// Reads the next line into `buf` without its line ending. Returns `None` at
+/// the end of the input and the UTF-8 error for undecodable lines.
+fn next_line<'b>(
+    reader: &mut impl BufRead,
+    buf: &'b mut Vec<u8>,
+) -> Result<Option<Result<&'b str, std::str::Utf8Error>>, Error> {
+    buf.clear();
+    if reader.read_until(b'\n', buf).map_err(Error::Input)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(std::str::from_utf8(buf).map(str::trim)))
+}
+
+// =============================================================================
+// JSONL Parser
+// =============================================================================
+
+/// Aggregates `k6 run --out json` points into a summary
+pub fn parse_jsonl(mut reader: impl BufRead, options: &ParseOptions) -> Result<K6Summary, Error> {
+    let mut stream = JsonlStream::new(options);
+    let mut buf = Vec::new();
+    while reader.read_until(b'\n', &mut buf).map_err(Error::Input)? > 0 {
+        stream.push_line(&buf)?;
+        buf.clear();
+    }
+    stream.finish()
+}
+
+/// Aggregates JSONL lines as they arrive, for output k6 is still writing.
+/// [`parse_jsonl`] reads a whole input through it.
+pub struct JsonlStream<'a> {
+    aggregator: Aggregator<'a>,
+    rolling: Option<RollingStats>,
+    lines: usize,
+}
+
+impl<'a> JsonlStream<'a> {
+    /// A stream with no lines yet
+    pub fn new(options: &'a ParseOptions) -> Self {
+        JsonlStream {
+            aggregator: Aggregator::new(options, |line, message| Error::Jsonl { line, message }),
+            rolling: None,
+            lines: 0,
+        }
+    }
+
+    /// Keeps [`RollingStats`] of the points so far, with the current rates
+    /// over the last `window_ms` of the run, instead of every point. Memory
+    /// then stays bounded however long the run is, and the summary has no
+    /// samples, endpoints or timeline and cannot be restricted to a time
+    /// window.
+    pub fn with_rolling_stats(mut self, window_ms: f64) -> Self {
+        let thresholds = self.aggregator.options.thresholds.clone();
+        self.rolling = Some(RollingStats::new(window_ms, thresholds));
+        self
+    }
+
+    /// The rolling stats, when enabled with [`JsonlStream::with_rolling_stats`]
+    pub fn rolling_stats(&self) -> Option<&RollingStats> {
+        self.rolling.as_ref()
+    }
+
+    /// Adds one line, with or without its line ending. Malformed lines are
+    /// skipped, or fail in strict mode.
+    pub fn push_line(&mut self, line: &[u8]) -> Result<(), Error> {
+        self.lines += 1;
+        let line = match std::str::from_utf8(line) {
+            Ok(line) => line.trim(),
+            Err(e) => return self.aggregator.skip(self.lines, e.to_string()),
+        };
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        let entry: JsonlLine = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => return self.aggregator.skip(self.lines, e.to_string()),
+        };
+        if entry.data.value.is_some_and(|value| !value.is_finite()) {
+            return self
+                .aggregator
+                .skip(self.lines, NON_FINITE_VALUE.to_string());
+        }
+        self.aggregator.parsed_lines += 1;
+
+        match entry.line_type.as_str() {
+            "Metric" => {
+                let metric_type = match entry.data.metric_type.as_deref() {
+                    Some("counter") => MetricType::Counter,
+                    Some("rate") => MetricType::Rate,
+                    Some("gauge") => MetricType::Gauge,
+                    Some("trend") => MetricType::Trend,
+                    _ => MetricType::Trend,
+                };
+                if let Some(rolling) = &mut self.rolling {
+                    rolling.declare(&entry.metric, metric_type, &entry.data.thresholds);
+                }
+                self.aggregator.declare(
+                    &entry.metric,
+                    metric_type,
+                    entry.data.contains.unwrap_or_default(),
+                    entry.data.thresholds,
+                );
+            }
+            "Point" => {
+                if let Some(value) = entry.data.value {
+                    let time = entry.data.time.as_deref().and_then(parse_timestamp);
+                    let tags = entry.data.tags.unwrap_or_default();
+                    let Some(rolling) = &mut self.rolling else {
+                        self.aggregator.add_point(&entry.metric, time, value, &tags);
+                        return Ok(());
+                    };
+                    self.aggregator.track_time(time);
+                    if matches_tag_filters(&self.aggregator.options.tag_filters, &tags) {
+                        let metric_type = self.aggregator.metric_type(&entry.metric);
+                        rolling.add_point(&entry.metric, metric_type, time, value);
+                        for submetric in &self.aggregator.submetrics {
+                            if submetric.parent == entry.metric && submetric.matches(&tags) {
+                                rolling.add_point(&submetric.name, metric_type, time, value);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The summary of every line added
+    pub fn finish(self) -> Result<K6Summary, Error> {
+        match &self.rolling {
+            Some(rolling) => self.aggregator.finish_running(rolling, NO_JSONL_LINES),
+            None => self.aggregator.finish(NO_JSONL_LINES),
+        }
+    }
+}
+
+// =============================================================================
+// CSV Parser (--out csv)
+// =============================================================================
+
+/// Header of k6's CSV output; every column other than these is a tag
+const CSV_METRIC_COLUMN: &str = "metric_name";
+const CSV_TIME_COLUMN: &str = "timestamp";
+const CSV_VALUE_COLUMN: &str = "metric_value";
+/// `key=value` pairs joined by `&`, holding tags without a column of their own
+const CSV_EXTRA_TAGS_COLUMN: &str = "extra_tags";
+/// Same encoding as `extra_tags`, for non-indexed metadata such as trace IDs
+const CSV_METADATA_COLUMN: &str = "metadata";
+
+/// Aggregates `k6 run --out csv` points into a summary
+pub fn parse_csv(mut reader: impl BufRead, options: &ParseOptions) -> Result<K6Summary, Error> {
+    let mut aggregator = Aggregator::new(options, |line, message| Error::Csv { line, message });
+    let mut buf = Vec::new();
+    let mut idx = 0;
+
+    let header = loop {
+        let Some(line) = next_line(&mut reader, &mut buf)? else {
+            return Err(Error::Format("no CSV header found".to_string()));
+        };
+        idx += 1;
+        match line {
+            Ok("") => continue,
+            Ok(line) => {
+                break split_csv_record(line)
+                    .map_err(|message| Error::Csv { line: idx, message })?
+            }
+            Err(e) => {
+                return Err(Error::Csv {
+                    line: idx,
+                    message: e.to_string(),
+                })
+            }
+        }
+    };
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| Error::Csv {
+                line: idx,
+                message: format!("missing '{}' column", name),
+            })
+    };
+    let metric_col = column(CSV_METRIC_COLUMN)?;
+    let time_col = column(CSV_TIME_COLUMN)?;
+    let value_col = column(CSV_VALUE_COLUMN)?;
+
+    while let Some(line) = next_line(&mut reader, &mut buf)? {
+        idx += 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                aggregator.skip(idx, e.to_string())?;
+                continue;
+            }
+        };
+        if line.is_empty() {
+            continue;
+        }
+
+        let record = match split_csv_record(line) {
+            Ok(record) if record.len() == header.len() => record,
+            Ok(record) => {
+                let message = format!("expected {} fields, found {}", header.len(), record.len());
+                aggregator.skip(idx, message)?;
+                continue;
+            }
+            Err(message) => {
+                aggregator.skip(idx, message)?;
+                continue;
+            }
+        };
+        let value = match record[value_col].parse::<f64>() {
+            Ok(value) if value.is_finite() => value,
+            Ok(_) => {
+                aggregator.skip(idx, NON_FINITE_VALUE.to_string())?;
+                continue;
+            }
+            Err(e) => {
+                aggregator.skip(idx, format!("invalid metric_value: {}", e))?;
+                continue;
+            }
+        };
+        aggregator.parsed_lines += 1;
+
+        let mut tags: HashMap<String, String> = HashMap::new();
+        for (name, field) in header.iter().zip(&record) {
+            match name.as_str() {
+                CSV_METRIC_COLUMN | CSV_TIME_COLUMN | CSV_VALUE_COLUMN | CSV_METADATA_COLUMN => {}
+                CSV_EXTRA_TAGS_COLUMN => {
+                    for pair in field.split('&').filter(|p| !p.is_empty()) {
+                        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                        tags.insert(key.to_string(), value.to_string());
+                    }
+                }
+                _ if !field.is_empty() => {
+                    tags.insert(name.clone(), field.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let time = parse_csv_timestamp(&record[time_col]);
+        aggregator.add_point(&record[metric_col], time, value, &tags);
+    }
+
+    aggregator.finish("no CSV rows found")
+}
+
+/// Splits one CSV record, honouring double-quoted fields with `""` escapes
+fn split_csv_record(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Parses a CSV timestamp into epoch milliseconds. k6 writes Unix seconds by
+/// default; the `timeFormat` option switches to RFC 3339 or a finer Unix unit,
+/// which is told apart by magnitude.
+fn parse_csv_timestamp(s: &str) -> Option<f64> {
+    if s.contains('T') {
+        return parse_timestamp(s);
+    }
+    let value: f64 = s.parse().ok()?;
+    Some(match value.abs() {
+        v if v >= 1e17 => value / 1e6,
+        v if v >= 1e14 => value / 1e3,
+        v if v >= 1e11 => value,
+        _ => value * 1000.0,
+    })
+}
+
+/// Parses an RFC 3339 timestamp as k6 writes it (e.g.
+/// `2017-05-09T14:34:45.625742514+02:00`) into milliseconds since the epoch.
+pub(crate) fn parse_timestamp(s: &str) -> Option<f64> {
+    let (date, time) = s.trim().split_once(['T', ' '])?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: i64 = date_parts.next()?.parse().ok()?;
+    let day: i64 = date_parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (clock, offset_ms) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0.0)
+    } else {
+        let idx = time.rfind(['+', '-'])?;
+        let (clock, offset) = time.split_at(idx);
+        let sign = if offset.starts_with('-') { -1.0 } else { 1.0 };
+        let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+        let hours: f64 = hours.parse().ok()?;
+        let minutes: f64 = minutes.parse().ok()?;
+        (clock, sign * (hours * 3_600_000.0 + minutes * 60_000.0))
+    };
+
+    let mut clock_parts = clock.splitn(3, ':');
+    let hours: f64 = clock_parts.next()?.parse().ok()?;
+    let minutes: f64 = clock_parts.next()?.parse().ok()?;
+    let seconds: f64 = clock_parts.next()?.parse().ok()?;
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let day_ms = (hours * 3600.0 + minutes * 60.0 + seconds) * 1000.0;
+    Some(days as f64 * 86_400_000.0 + day_ms - offset_ms)
+}
+
+// =============================================================================
+// k6 Script Options
+// =============================================================================
+
+/// Extracts `export const options = {...}` from a k6 script without running it.
+///
+/// Only literal values are understood; anything computed at runtime (such as
+/// `__ENV.VUS || 10`) is left out of the resulting options.
+pub fn parse_script_options(script: &str) -> Result<TestOptions, String> {
+    let start = find_options_literal(script).ok_or(
+        "no `options` object literal found in script (expected `export const options = {...}`)",
+    )?;
+
+    let mut parser = JsLiteralParser {
+        src: script.as_bytes(),
+        pos: start,
+    };
+    let value = parser
+        .parse_literal()?
+        .ok_or_else(|| parser.error("`options` is not an object literal"))?;
+    if !value.is_object() {
+        return Err(parser.error("`options` is not an object literal"));
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("unsupported options: {}", e))
+}
+
+/// Position of the `{` of the options object literal.
+///
+/// Declarations inside comments and string literals are ignored.
+fn find_options_literal(script: &str) -> Option<usize> {
+    let code = mask_comments_and_strings(script);
+    const DECLARATIONS: [&str; 6] = [
+        "export const options",
+        "export let options",
+        "export var options",
+        "const options",
+        "let options",
+        "var options",
+    ];
+
+    for decl in DECLARATIONS {
+        let mut from = 0;
+        while let Some(found) = code[from..].find(decl) {
+            let after = from + found + decl.len();
+            from = after;
+            // Skip `optionsFoo` and an optional TypeScript type annotation
+            let rest = &code[after..];
+            if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$') {
+                continue;
+            }
+            let Some(eq) = rest.find('=') else { continue };
+            let rest_after_eq = rest[eq + 1..].trim_start();
+            if rest_after_eq.starts_with('{') {
+                return Some(code.len() - rest_after_eq.len());
+            }
+        }
+    }
+    None
+}
+
+/// Replaces comments and string literals with spaces, keeping byte offsets
+/// and line breaks so positions still point into the original script.
+fn mask_comments_and_strings(script: &str) -> String {
+    let src = script.as_bytes();
+    let mut out = src.to_vec();
+    let mut mask = |from: usize, to: usize| {
+        for b in &mut out[from..to] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+    };
+
+    let mut i = 0;
+    while i < src.len() {
+        let start = i;
+        if src[i..].starts_with(b"//") {
+            while i < src.len() && src[i] != b'\n' {
+                i += 1;
+            }
+        } else if src[i..].starts_with(b"/*") {
+            i += 2;
+            while i < src.len() && !src[i..].starts_with(b"*/") {
+                i += 1;
+            }
+            i = (i + 2).min(src.len());
+        } else if matches!(src[i], b'"' | b'\'' | b'`') {
+            let quote = src[i];
+            i += 1;
+            while i < src.len() && src[i] != quote {
+                if src[i] == b'\\' {
+                    i += 1;
+                } else if src[i] == b'\n' && quote != b'`' {
+                    break;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(src.len());
+        } else {
+            i += 1;
+            continue;
+        }
+        mask(start, i);
+    }
+
+    // Only whole ASCII-delimited runs were replaced, so this stays valid UTF-8
+    String::from_utf8(out).unwrap_or_else(|_| script.to_string())
+}
+
+struct JsLiteralParser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl JsLiteralParser<'_> {
+    fn error(&self, msg: &str) -> String {
+        let line = self.src[..self.pos.min(self.src.len())]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+            + 1;
+        format!("line {}: {}", line, msg)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_trivia(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_ascii_whitespace() {
+                self.pos += 1;
+            } else if self.src[self.pos..].starts_with(b"//") {
+                while self.peek().is_some_and(|c| c != b'\n') {
+                    self.pos += 1;
+                }
+            } else if self.src[self.pos..].starts_with(b"/*") {
+                self.pos += 2;
+                while self.pos < self.src.len() && !self.src[self.pos..].starts_with(b"*/") {
+                    self.pos += 1;
+                }
+                self.pos = (self.pos + 2).min(self.src.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn at_value_end(&mut self) -> bool {
+        self.skip_trivia();
+        matches!(self.peek(), None | Some(b',' | b'}' | b']'))
+    }
+
+    /// Parses a value; `Ok(None)` means a non-literal expression that was skipped.
+    fn parse_value(&mut self) -> Result<Option<serde_json::Value>, String> {
+        let value = self.parse_literal()?;
+        if self.at_value_end() {
+            Ok(value)
+        } else {
+            // e.g. `60 * 1000` or `__ENV.VUS || 10`
+            self.skip_expression()?;
+            Ok(None)
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<Option<serde_json::Value>, String> {
+        self.skip_trivia();
+        Ok(match self.peek() {
+            None => return Err(self.error("unexpected end of script")),
+            Some(b'{') => self.parse_object()?,
+            Some(b'[') => self.parse_array()?,
+            Some(b'"' | b'\'') => self.parse_string()?.map(serde_json::Value::String),
+            Some(b'`') => self.parse_template()?.map(serde_json::Value::String),
+            Some(c) if c.is_ascii_digit() || c == b'-' || c == b'.' => self.parse_number(),
+            Some(_) => self.parse_keyword(),
+        })
+    }
+
+    fn parse_object(&mut self) -> Result<Option<serde_json::Value>, String> {
+        self.pos += 1;
+        let mut map = serde_json::Map::new();
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                None => return Err(self.error("unterminated object literal")),
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Some(serde_json::Value::Object(map)));
+                }
+                Some(b',') => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let key = match self.peek() {
+                Some(b'"' | b'\'') => self.parse_string()?,
+                Some(b'[') | Some(b'.') => {
+                    // Computed keys and spreads cannot be resolved statically
+                    self.skip_expression()?;
+                    continue;
+                }
+                _ => self.parse_identifier(),
+            };
+            let Some(key) = key.filter(|k| !k.is_empty()) else {
+                return Err(self.error("expected property name"));
+            };
+
+            self.skip_trivia();
+            match self.peek() {
+                Some(b':') => {
+                    self.pos += 1;
+                    if let Some(value) = self.parse_value()? {
+                        map.insert(key, value);
+                    }
+                }
+                // Shorthand `{ thresholds }` or a method definition
+                _ => self.skip_expression()?,
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Option<serde_json::Value>, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                None => return Err(self.error("unterminated array literal")),
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Some(serde_json::Value::Array(items)));
+                }
+                Some(b',') => self.pos += 1,
+                _ => {
+                    if let Some(value) = self.parse_value()? {
+                        items.push(value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Option<String>, String> {
+        let start = self.pos;
+        let quote = self.src[self.pos];
+        self.pos += 1;
+        let mut out = Vec::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    out.push(match escaped {
+                        b'n' => b'\n',
+                        b't' => b'\t',
+                        b'r' => b'\r',
+                        other => other,
+                    });
+                }
+                c if c == quote => return Ok(Some(String::from_utf8_lossy(&out).into_owned())),
+                b'\n' => break,
+                c => out.push(c),
+            }
+        }
+        self.pos = start;
+        Err(self.error("unterminated string literal"))
+    }
+
+    /// Template literals with `${...}` substitutions are not static.
+    fn parse_template(&mut self) -> Result<Option<String>, String> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut dynamic = false;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                b'\\' => self.pos += 1,
+                b'$' if self.peek() == Some(b'{') => dynamic = true,
+                b'`' => {
+                    let text = String::from_utf8_lossy(&self.src[start..self.pos - 1]).into_owned();
+                    return Ok((!dynamic).then_some(text));
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("unterminated template literal"))
+    }
+
+    fn parse_number(&mut self) -> Option<serde_json::Value> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, b'.' | b'_' | b'-' | b'+'))
+        {
+            // A sign is only part of the number at the start or after an exponent
+            let c = self.src[self.pos];
+            if (c == b'-' || c == b'+')
+                && self.pos != start
+                && !matches!(self.src[self.pos - 1], b'e' | b'E')
+            {
+                break;
+            }
+            self.pos += 1;
+        }
+        let text: String = String::from_utf8_lossy(&self.src[start..self.pos]).replace('_', "");
+        let number = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X"))
+        {
+            i64::from_str_radix(hex, 16).ok().map(|n| n as f64)
+        } else {
+            text.parse::<f64>().ok()
+        }?;
+
+        Some(
+            if number.fract() == 0.0 && number.abs() < 9_007_199_254_740_992.0 {
+                serde_json::Value::from(number as i64)
+            } else {
+                serde_json::Value::from(number)
+            },
+        )
+    }
+
+    fn parse_identifier(&mut self) -> Option<String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c >= 0x80)
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| String::from_utf8_lossy(&self.src[start..self.pos]).into_owned())
+    }
+
+    fn parse_keyword(&mut self) -> Option<serde_json::Value> {
+        match self.parse_identifier()?.as_str() {
+            "true" => Some(serde_json::Value::Bool(true)),
+            "false" => Some(serde_json::Value::Bool(false)),
+            _ => None,
+        }
+    }
+
+    /// Skips to the end of the current expression: the next `,`, `}` or `]`
+    /// at the same nesting depth.
+    fn skip_expression(&mut self) -> Result<(), String> {
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            match c {
+                b'"' | b'\'' => {
+                    self.parse_string()?;
+                    continue;
+                }
+                b'`' => {
+                    self.parse_template()?;
+                    continue;
+                }
+                b'/' if self.src[self.pos..].starts_with(b"//")
+                    || self.src[self.pos..].starts_with(b"/*") =>
+                {
+                    self.skip_trivia();
+                    continue;
+                }
+                b'{' | b'[' | b'(' => depth += 1,
+                b'}' | b']' | b')' if depth > 0 => depth -= 1,
+                b',' | b'}' | b']' if depth == 0 => return Ok(()),
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        Err(self.error("unexpected end of script"))
+    }
+}
+
+// =============================================================================
+// Durations
+// =============================================================================
+
+/// Parses a Go/k6 duration such as `1m30s`, `500ms` or `2h`. A bare number is
+/// taken as seconds, matching k6's option parsing.
+pub fn parse_duration_ms(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    if let Ok(secs) = s.parse::<f64>() {
+        return Some(secs * 1000.0);
+    }
+
+    let mut total = 0.0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let num_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if num_len == 0 {
+            return None;
+        }
+        let value: f64 = rest[..num_len].parse().ok()?;
+        rest = &rest[num_len..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let factor = match &rest[..unit_len] {
+            "ns" => 1e-6,
+            "us" | "µs" => 1e-3,
+            "ms" => 1.0,
+            "s" => 1000.0,
+            "m" => 60_000.0,
+            "h" => 3_600_000.0,
+            "d" => 86_400_000.0,
+            _ => return None,
+        };
+        total += value * factor;
+        rest = &rest[unit_len..];
+    }
+    Some(total)
+}
+
+// =============================================================================
+// Summary Export Conversion
+// =============================================================================
+
+/// Converts a legacy `--summary-export` document into a summary
+pub fn parse_summary_export(content: &str) -> Result<K6Summary, Error> {
+    read_summary_export(&mut content.as_bytes())
+}
+
+fn read_summary_export(input: &mut dyn BufRead) -> Result<K6Summary, Error> {
+    let export: SummaryExport = read_json(input)?;
+
+    let metrics: HashMap<String, Metric> = export
+        .metrics
+        .into_iter()
+        .map(|(name, metric)| {
+            let converted = convert_export_metric(&name, metric);
+            (name, converted)
+        })
+        .collect();
+
+    // The export has no run duration, but k6 derives every counter rate from
+    // it, so any non-zero counter gives it back
+    let mut counters: Vec<(&String, &Metric)> = metrics
+        .iter()
+        .filter(|(_, m)| m.metric_type == MetricType::Counter)
+        .collect();
+    counters.sort_by_key(|(name, _)| *name);
+    let duration_ms = counters.into_iter().find_map(|(_, metric)| {
+        let count = metric.values.get("count")?;
+        let rate = metric.values.get("rate").filter(|r| **r > 0.0)?;
+        Some(count / rate * 1000.0)
+    });
+
+    Ok(K6Summary {
+        metrics,
+        root_group: export.root_group,
+        state: duration_ms.map(|ms| State {
+            test_run_duration_ms: ms,
+        }),
+        ..K6Summary::default()
+    })
+}
+
+/// The export drops the metric type, so it is inferred from the stats present
+fn convert_export_metric(name: &str, metric: ExportMetric) -> Metric {
+    let mut values = metric.values;
+    let has = |key: &str| values.contains_key(key);
+
+    let (builtin_type, builtin_contains) = builtin_metric(name);
+    let metric_type = if has("passes") || has("fails") {
+        MetricType::Rate
+    } else if has("count") && has("rate") {
+        MetricType::Counter
+    } else if has("avg") || has("med") {
+        MetricType::Trend
+    } else if has("value") {
+        MetricType::Gauge
+    } else {
+        builtin_type
+    };
+    let contains = if metric_type == builtin_type {
+        builtin_contains.to_string()
+    } else {
+        String::new()
+    };
+
+    // A rate's ratio is exported as "value"
+    if metric_type == MetricType::Rate {
+        if let Some(rate) = values.remove("value") {
+            values.insert("rate".to_string(), rate);
+        }
+    }
+
+    let thresholds = metric
+        .thresholds
+        .into_iter()
+        .map(|(expr, failed)| {
+            let threshold = Threshold {
+                ok: !failed,
+                unknown: false,
+            };
+            (expr, threshold)
+        })
+        .collect();
+
+    Metric {
+        metric_type,
+        contains,
+        values,
+        thresholds,
+    }
+}
+
+// =============================================================================
+// Format Detection
+// =============================================================================
+
+/// The k6 output formats k6r reads
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+#[non_exhaustive]
+pub enum FileFormat {
+    /// handleSummary `data` as JSON
+    HandleSummary,
+    /// Legacy `k6 run --summary-export` output
+    SummaryExport,
+    /// `k6 run --out json` points
+    Jsonl,
+    /// `k6 run --out csv` points
+    Csv,
+}
+
+impl FileFormat {
+    /// Every format, in the order ties in detection are broken
+    pub const ALL: [FileFormat; 4] = [
+        FileFormat::HandleSummary,
+        FileFormat::SummaryExport,
+        FileFormat::Jsonl,
+        FileFormat::Csv,
+    ];
+
+    /// The sniffer and parser of this format
+    pub fn input(self) -> &'static dyn InputFormat {
+        match self {
+            FileFormat::HandleSummary => &HandleSummaryInput,
+            FileFormat::SummaryExport => &SummaryExportInput,
+            FileFormat::Jsonl => &JsonlInput,
+            FileFormat::Csv => &CsvInput,
+        }
+    }
+
+    /// Whole-run summaries, as opposed to streams of metric points
+    pub fn is_summary(self) -> bool {
+        self.input().is_summary()
+    }
+
+    /// Human-readable name, for messages
+    pub fn name(self) -> &'static str {
+        self.input().name()
+    }
+}
+
+/// How sure a format is that it can read an input, judged from its head
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Confidence {
+    /// Not this format
+    No,
+    /// Could be this format
+    Low,
+    /// Probably this format
+    Medium,
+    /// Certainly this format
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Confidence::No => "no",
+            Confidence::Low => "low",
+            Confidence::Medium => "medium",
+            Confidence::High => "high",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One k6 output format: a cheap look at the head of an input, and a parser
+/// for the whole of it
+pub trait InputFormat: Sync {
+    /// Human-readable name, for messages
+    fn name(&self) -> &'static str;
+
+    /// Whole-run summaries, as opposed to streams of metric points
+    fn is_summary(&self) -> bool;
+
+    /// Judges the first lines of an input without parsing the rest of it
+    fn sniff(&self, head: &str) -> Confidence;
+
+    /// Reads the whole input into a summary
+    fn parse(&self, input: &mut dyn BufRead, options: &ParseOptions) -> Result<K6Summary, Error>;
+}
+
+/// Deserializes a whole JSON document from the stream. Errors in a value
+/// name its path, e.g. `metrics.http_reqs.count`.
+fn read_json<T: serde::de::DeserializeOwned>(input: &mut dyn BufRead) -> Result<T, Error> {
+    let into_error = |e: serde_json::Error| {
+        if e.is_io() {
+            Error::Input(e.into())
+        } else {
+            Error::Json(e)
+        }
+    };
+    let mut deserializer = serde_json::Deserializer::from_reader(input);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        if inner.is_data() && path != "." {
+            into_error(serde::de::Error::custom(format!("{}: {}", path, inner)))
+        } else {
+            into_error(inner)
+        }
+    })?;
+    deserializer.end().map_err(into_error)?;
+    Ok(value)
+}
+
+struct HandleSummaryInput;
+
+impl InputFormat for HandleSummaryInput {
+    fn name(&self) -> &'static str {
+        "handleSummary JSON"
+    }
+
+    fn is_summary(&self) -> bool {
+        true
+    }
+
+    fn sniff(&self, head: &str) -> Confidence {
+        match summary_shape(head) {
+            Some(SummaryShape::Typed) => Confidence::High,
+            Some(SummaryShape::Untyped) => Confidence::Low,
+            Some(SummaryShape::Unknown) => Confidence::Medium,
+            None if head.trim_start().starts_with('{') && !first_line_is_jsonl(head) => {
+                Confidence::Low
+            }
+            None => Confidence::No,
+        }
+    }
+
+    fn parse(&self, input: &mut dyn BufRead, _: &ParseOptions) -> Result<K6Summary, Error> {
+        read_json(input)
+    }
+}
+
+struct SummaryExportInput;
+
+impl InputFormat for SummaryExportInput {
+    fn name(&self) -> &'static str {
+        "summary export JSON (--summary-export)"
+    }
+
+    fn is_summary(&self) -> bool {
+        true
+    }
+
+    fn sniff(&self, head: &str) -> Confidence {
+        match summary_shape(head) {
+            Some(SummaryShape::Untyped) => Confidence::High,
+            Some(SummaryShape::Unknown) => Confidence::Low,
+            Some(SummaryShape::Typed) | None => Confidence::No,
+        }
+    }
+
+    fn parse(&self, input: &mut dyn BufRead, _: &ParseOptions) -> Result<K6Summary, Error> {
+        read_summary_export(input)
+    }
+}
+
+struct JsonlInput;
+
+impl InputFormat for JsonlInput {
+    fn name(&self) -> &'static str {
+        "JSONL (--out json)"
+    }
+
+    fn is_summary(&self) -> bool {
+        false
+    }
+
+    fn sniff(&self, head: &str) -> Confidence {
+        let Some(line) = first_line(head) else {
+            return Confidence::No;
+        };
+        if !line.starts_with('{') {
+            return Confidence::No;
+        }
+        // Every JSONL line is a self-contained {"type", "metric", "data"} record
+        if first_line_is_jsonl(head) {
+            return Confidence::High;
+        }
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) if value.get("metrics").is_some() => Confidence::No,
+            Ok(_) => Confidence::Medium,
+            // The start of a pretty-printed summary rather than a record
+            Err(_) if line == "{" || line.contains("\"metrics\"") => Confidence::Low,
+            // Otherwise a malformed JSONL line, reported by the parser
+            Err(_) => Confidence::Medium,
+        }
+    }
+
+    fn parse(&self, input: &mut dyn BufRead, options: &ParseOptions) -> Result<K6Summary, Error> {
+        parse_jsonl(input, options)
+    }
+}
+
+struct CsvInput;
+
+impl InputFormat for CsvInput {
+    fn name(&self) -> &'static str {
+        "CSV (--out csv)"
+    }
+
+    fn is_summary(&self) -> bool {
+        false
+    }
+
+    fn sniff(&self, head: &str) -> Confidence {
+        match first_line(head) {
+            Some(line) if line.split(',').next() == Some(CSV_METRIC_COLUMN) => Confidence::High,
+            _ => Confidence::No,
+        }
+    }
+
+    fn parse(&self, input: &mut dyn BufRead, options: &ParseOptions) -> Result<K6Summary, Error> {
+        parse_csv(input, options)
+    }
+}
+
+fn first_line(head: &str) -> Option<&str> {
+    head.lines().map(str::trim).find(|l| !l.is_empty())
+}
+
+fn first_line_is_jsonl(head: &str) -> bool {
+    first_line(head).is_some_and(|line| serde_json::from_str::<JsonlLine>(line).is_ok())
+}
+
+/// What the metrics of a summary look like in the head of an input
+enum SummaryShape {
+    /// handleSummary metrics carry a `type` and nest stats under `values`
+    Typed,
+    /// `--summary-export` puts stats directly on the metric
+    Untyped,
+    /// No metric seen yet
+    Unknown,
+}
+
+/// Tells the summary kinds apart by the keys following `"metrics"`, without
+/// parsing the document, so it also works on a truncated one
+fn summary_shape(head: &str) -> Option<SummaryShape> {
+    let trimmed = head.trim_start();
+    if !trimmed.starts_with('{') || first_line_is_jsonl(head) {
+        return None;
+    }
+    let Some(start) = head.find("\"metrics\"") else {
+        return (first_line(head) == Some("{")).then_some(SummaryShape::Unknown);
+    };
+    let metrics = &head[start..];
+
+    let typed = ["counter", "gauge", "rate", "trend"]
+        .iter()
+        .any(|kind| key_followed_by(metrics, "type", &format!("\"{}\"", kind)))
+        || key_followed_by(metrics, "values", "{");
+    let untyped = ["count", "rate", "value", "avg"]
+        .iter()
+        .any(|stat| key_number_follows(metrics, stat));
+    Some(if typed {
+        SummaryShape::Typed
+    } else if untyped {
+        SummaryShape::Untyped
+    } else {
+        SummaryShape::Unknown
+    })
+}
+
+/// Whether `"key": <value>` occurs, with `value` a prefix of what follows
+fn key_followed_by(text: &str, key: &str, value: &str) -> bool {
+    values_after_key(text, key).any(|rest| rest.starts_with(value))
+}
+
+/// Whether `"key": <number>` occurs
+fn key_number_follows(text: &str, key: &str) -> bool {
+    values_after_key(text, key)
+        .any(|rest| rest.starts_with(|c: char| c.is_ascii_digit() || c == '-'))
+}
+
+/// The text after each `"key":`, leading whitespace removed
+fn values_after_key<'a>(text: &'a str, key: &str) -> impl Iterator<Item = &'a str> {
+    let quoted = format!("\"{}\"", key);
+    let positions: Vec<usize> = text.match_indices(&quoted).map(|(i, _)| i).collect();
+    positions.into_iter().filter_map(move |i| {
+        text[i + quoted.len()..]
+            .trim_start()
+            .strip_prefix(':')
+            .map(str::trim_start)
+    })
+}
+
+/// The format chosen for an input, and how it was chosen
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Detection {
+    /// The format the input is parsed as
+    pub format: FileFormat,
+    /// `None` when the format was given rather than detected
+    pub confidence: Option<Confidence>,
+    /// Other formats the input might be, most likely first
+    pub alternatives: Vec<(FileFormat, Confidence)>,
+}
+
+/// Sniffs the head of an input with every format and picks the most
+/// confident one, earlier formats winning ties.
+pub fn detect_format(head: &str) -> Result<Detection, Error> {
+    if first_line(head).is_none() {
+        return Err(Error::Format("input is empty".to_string()));
+    }
+
+    let mut candidates: Vec<(FileFormat, Confidence)> = FileFormat::ALL
+        .into_iter()
+        .map(|format| (format, format.input().sniff(head)))
+        .filter(|(_, confidence)| *confidence > Confidence::No)
+        .collect();
+    candidates.sort_by_key(|&(_, confidence)| std::cmp::Reverse(confidence));
+
+    let mut candidates = candidates.into_iter();
+    let Some((format, confidence)) = candidates.next() else {
+        return Err(Error::Format(
+            "expected a handleSummary JSON object, JSONL lines or a k6 CSV header".to_string(),
+        ));
+    };
+    Ok(Detection {
+        format,
+        confidence: Some(confidence),
+        alternatives: candidates.collect(),
+    })
+}
+
+// =============================================================================
+// Input Decompression
+// =============================================================================
+
+/// Path standing for stdin as an input and stdout as an output
+pub const STDIO_PATH: &str = "-";
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Opens a results file, or stdin for `-`, transparently decompressing gzip
+/// and zstd
+pub fn open_input(path: &Path) -> Result<Box<dyn BufRead>, Error> {
+    let read_error = |source| Error::Read {
+        path: path.to_path_buf(),
+        source,
+    };
+    if path == Path::new(STDIO_PATH) {
+        return decompress(std::io::stdin().lock()).map_err(read_error);
+    }
+    let file = std::fs::File::open(path).map_err(read_error)?;
+    decompress(BufReader::new(file)).map_err(read_error)
+}
+
+/// Wraps the reader in a gzip or zstd decoder when its magic bytes say so.
+fn decompress<R: BufRead + 'static>(mut reader: R) -> std::io::Result<Box<dyn BufRead>> {
+    let head = reader.fill_buf()?;
+    if head.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else if head.starts_with(&ZSTD_MAGIC) {
+        Ok(Box::new(BufReader::new(ZstdReader::new(reader)?)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Decodes all frames of a zstd stream; concatenated archives have several.
+struct ZstdReader<R: BufRead> {
+    decoder: Option<StreamingDecoder<R, FrameDecoder>>,
+}
+
+impl<R: BufRead> ZstdReader<R> {
+    fn new(reader: R) -> std::io::Result<Self> {
+        let decoder = StreamingDecoder::new(reader).map_err(std::io::Error::other)?;
+        Ok(ZstdReader {
+            decoder: Some(decoder),
+        })
+    }
+}
+
+impl<R: BufRead> Read for ZstdReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let Some(decoder) = self.decoder.as_mut() else {
+                return Ok(0);
+            };
+            let n = decoder.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+
+            // Frame finished; continue with the next one if any input remains
+            let mut source = self
+                .decoder
+                .take()
+                .map(StreamingDecoder::into_inner)
+                .unwrap();
+            if source.fill_buf()?.is_empty() {
+                return Ok(0);
+            }
+            self.decoder = Some(StreamingDecoder::new(source).map_err(std::io::Error::other)?);
+        }
+    }
+}
+
+/// Most of an input read for format detection, unless a format is sure sooner
+const MAX_HEAD_LEN: usize = 64 * 1024;
+
+/// Reads whole lines until one format is certain, [`MAX_HEAD_LEN`] bytes
+/// have been read or the input ends, and detects the format from them.
+fn read_head(reader: &mut impl BufRead) -> Result<(String, Detection), Error> {
+    let mut head = Vec::new();
+    loop {
+        let start = head.len();
+        let eof = reader.read_until(b'\n', &mut head).map_err(Error::Input)? == 0;
+        if !eof && head[start..].trim_ascii().is_empty() {
+            continue;
+        }
+        let text = std::str::from_utf8(&head)
+            .map_err(|_| Error::Format("input is not UTF-8 text".to_string()))?;
+        let detection = detect_format(text);
+        let sure = matches!(&detection, Ok(d) if d.confidence == Some(Confidence::High));
+        if eof || sure || head.len() >= MAX_HEAD_LEN {
+            let head = String::from_utf8(head).expect("checked above");
+            return detection.map(|detection| (head, detection));
+        }
+    }
+}
+
+// =============================================================================
+// Loading Inputs
+// =============================================================================
+
+/// An opened input whose format is known but not yet parsed
+pub struct DetectedInput {
+    /// How the format was chosen
+    pub detection: Detection,
+    reader: Box<dyn BufRead>,
+}
+
+impl DetectedInput {
+    /// Parses the whole input in the detected format
+    pub fn parse(mut self, options: &ParseOptions) -> Result<K6Summary, Error> {
+        self.detection
+            .format
+            .input()
+            .parse(&mut self.reader, options)
+    }
+}
+
+/// Opens an input file and detects its format unless one is given
+pub fn open_detected(path: &Path, format: Option<FileFormat>) -> Result<DetectedInput, Error> {
+    let mut input = open_input(path)?;
+    let (head, detection) = match format {
+        Some(format) => (
+            String::new(),
+            Detection {
+                format,
+                confidence: None,
+                alternatives: Vec::new(),
+            },
+        ),
+        None => read_head(&mut input)?,
+    };
+    Ok(DetectedInput {
+        detection,
+        reader: Box::new(std::io::Cursor::new(head).chain(input)),
+    })
+}
+
+/// Opens, detects and parses one input file
+pub fn load_input(
+    path: &Path,
+    format: Option<FileFormat>,
+    options: &ParseOptions,
+) -> Result<(Detection, K6Summary), Error> {
+    let input = open_detected(path, format)?;
+    let detection = input.detection.clone();
+    Ok((detection, input.parse(options)?))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::{RunOverview, TimeBound, Verdict};
+    use crate::render::{collect_checks, generate_report, ReportOptions};
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(
+            parse_timestamp("2024-01-01T10:00:00.250+00:00"),
+            Some(1_704_103_200_250.0)
+        );
+        assert_eq!(
+            parse_timestamp("2024-01-01T12:00:00.250+02:00"),
+            parse_timestamp("2024-01-01T10:00:00.250Z")
+        );
+        assert_eq!(
+            parse_timestamp("2024-03-01T00:00:00-05:30"),
+            Some(1_709_271_000_000.0)
+        );
+        assert_eq!(parse_timestamp("10:00:00"), None);
+    }
+
+    #[test]
+    fn test_parse_jsonl_across_midnight() {
+        let content = r#"{"type":"Point","data":{"time":"2024-01-01T23:59:59.000+00:00","value":1,"tags":null},"metric":"vus"}
+{"type":"Point","data":{"time":"2024-01-02T00:00:01.000+00:00","value":1,"tags":null},"metric":"vus"}"#;
+
+        let summary = parse_jsonl(content.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(summary.state.unwrap().test_run_duration_ms, 2000.0);
+    }
+
+    /// `tests/fixtures/k6-run.jsonl` and `k6-run-summary.json` describe the same
+    /// run: the raw `--out json` points and the handleSummary values k6's metric
+    /// sinks produce from them. They are written by hand in k6's output layout
+    /// until `tests/fixtures/record.sh` records them from a real run of
+    /// `k6-run.js`; the k6 version they come from then goes here.
+    #[test]
+    fn test_parse_jsonl_matches_handle_summary() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let expected: K6Summary =
+            serde_json::from_str(include_str!("../tests/fixtures/k6-run-summary.json")).unwrap();
+
+        let options = ParseOptions {
+            trend_stats: ["avg", "min", "med", "max", "p(90)", "p(95)"]
+                .iter()
+                .map(|s| s.parse().unwrap())
+                .collect(),
+            run_duration_ms: expected.state.as_ref().map(|s| s.test_run_duration_ms),
+            ..ParseOptions::default()
+        };
+        let actual = parse_jsonl(jsonl.as_bytes(), &options).unwrap();
+
+        let mut expected_names: Vec<&String> = expected.metrics.keys().collect();
+        let mut actual_names: Vec<&String> = actual.metrics.keys().collect();
+        expected_names.sort();
+        actual_names.sort();
+        assert_eq!(actual_names, expected_names);
+
+        for (name, want) in &expected.metrics {
+            let got = &actual.metrics[name];
+            assert_eq!(got.metric_type, want.metric_type, "{}", name);
+            for (key, want_value) in &want.values {
+                let got_value = got.values.get(key).copied();
+                let got_value = got_value.unwrap_or_else(|| panic!("{} is missing {}", name, key));
+                assert!(
+                    (got_value - want_value).abs() <= 1e-6,
+                    "{} {}: got {}, k6 reported {}",
+                    name,
+                    key,
+                    got_value,
+                    want_value
+                );
+            }
+        }
+    }
+
+    /// `tests/fixtures/k6-summary-export.json` is the fixture run's summary in
+    /// the legacy `--summary-export` layout
+    #[test]
+    fn test_parse_summary_export() {
+        let content = include_str!("../tests/fixtures/k6-summary-export.json");
+        let expected: K6Summary =
+            serde_json::from_str(include_str!("../tests/fixtures/k6-run-summary.json")).unwrap();
+        let summary = parse_summary_export(content).unwrap();
+
+        for (name, want) in &expected.metrics {
+            let got = &summary.metrics[name];
+            assert_eq!(got.metric_type, want.metric_type, "{}", name);
+            assert_eq!(got.values, want.values, "{}", name);
+        }
+        assert_eq!(summary.metrics["http_req_duration"].contains, "time");
+
+        let duration = summary.state.as_ref().unwrap().test_run_duration_ms;
+        let want = expected.state.unwrap().test_run_duration_ms;
+        assert!((duration - want).abs() < 1e-6, "{} != {}", duration, want);
+
+        let checks = collect_checks(summary.root_group.as_ref().unwrap());
+        let names: Vec<&str> = checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["status is 200"]);
+        assert_eq!(checks[0].fails, 2);
+    }
+
+    #[test]
+    fn test_summary_errors_name_the_path() {
+        let export = r#"{"metrics": {"http_reqs": {"count": 3, "thresholds": {"rate>1": "no"}}}}"#;
+        let err = parse_summary_export(export).unwrap_err().to_string();
+        assert!(err.contains("metrics.http_reqs"), "{}", err);
+
+        let summary = r#"{"metrics": {"vus": {"type": "gauge", "values": {"max": "ten"}}}}"#;
+        let err = HandleSummaryInput.parse(&mut summary.as_bytes(), &ParseOptions::default());
+        let err = err.unwrap_err().to_string();
+        assert!(err.contains("metrics.vus.values.max"), "{}", err);
+        assert!(err.contains("line 1"), "{}", err);
+
+        let err = parse_summary_export("{} {}").unwrap_err().to_string();
+        assert!(err.contains("trailing characters"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_summary_export_thresholds_and_groups() {
+        // The export's threshold values are true when the threshold failed
+        let content = r#"{
+            "metrics": {
+                "http_req_duration": {"avg": 164.5, "p(95)": 314.2, "thresholds": {"p(95)<500": false}},
+                "http_req_failed": {"passes": 2, "fails": 42, "value": 0.045, "thresholds": {"rate<0.01": true}},
+                "http_reqs": {"count": 44, "rate": 4.4}
+            },
+            "root_group": {
+                "name": "", "path": "", "id": "d41d8cd98f00b204e9800998ecf8427e",
+                "checks": {},
+                "groups": {
+                    "contacts": {
+                        "name": "contacts", "path": "::contacts", "id": "18e2a497efe6bf3cbbbf169fc2fb9188",
+                        "checks": {
+                            "page loaded": {"name": "page loaded", "path": "::contacts::page loaded",
+                                "id": "0b1095313b8206e31ee704b708994a7f", "passes": 21, "fails": 0}
+                        },
+                        "groups": {}
+                    }
+                }
+            }
+        }"#;
+        let summary = parse_summary_export(content).unwrap();
+
+        let failed = &summary.metrics["http_req_failed"].thresholds["rate<0.01"];
+        assert!(!failed.ok && !failed.unknown);
+        assert!(summary.metrics["http_req_duration"].thresholds["p(95)<500"].ok);
+
+        let report = generate_report(&summary, &ReportOptions::default());
+        assert!(report.contains("| http_req_failed | `rate<0.01` | ✗ **FAIL** |"));
+        assert!(report.contains("| ✓ page loaded | 21 | 0 | 100.00% |"));
+    }
+
+    #[test]
+    fn test_parse_jsonl_time_window() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let options = ParseOptions {
+            window: WindowOptions {
+                from: Some(TimeBound::Offset(3000.0)),
+                to: Some(TimeBound::Offset(7000.0)),
+                steady_state: false,
+            },
+            ..ParseOptions::default()
+        };
+        let summary = parse_jsonl(jsonl.as_bytes(), &options).unwrap();
+        let whole = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(summary.state.as_ref().unwrap().test_run_duration_ms, 4000.0);
+        let count = summary.metrics["http_reqs"].values["count"];
+        assert!(count < whole.metrics["http_reqs"].values["count"]);
+        assert_eq!(count / 4.0, summary.metrics["http_reqs"].values["rate"]);
+        let endpoint_requests: usize = summary.endpoints.iter().map(|e| e.requests).sum();
+        assert_eq!(endpoint_requests as f64, count);
+        assert_eq!(summary.timeline.first().unwrap().start_ms, 3000.0);
+
+        let report = generate_report(&summary, &ReportOptions::default());
+        assert!(report.contains("**Time Window:** 0:03 – 0:07 of the 0:10 run\n"));
+    }
+
+    #[test]
+    fn test_parse_jsonl_tag_filters() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let options = ParseOptions {
+            tag_filters: vec!["name=*contacts*".parse().unwrap()],
+            thresholds: BTreeMap::from([(
+                "http_req_duration".to_string(),
+                vec!["p(95)<500".to_string()],
+            )]),
+            ..ParseOptions::default()
+        };
+        let summary = parse_jsonl(jsonl.as_bytes(), &options).unwrap();
+
+        assert_eq!(summary.metrics["http_reqs"].values["count"], 21.0);
+        assert_eq!(summary.endpoints.len(), 1);
+        // The run's span still covers every point
+        let whole = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(
+            summary.state.as_ref().unwrap().test_run_duration_ms,
+            whole.state.unwrap().test_run_duration_ms
+        );
+
+        let report = generate_report(&summary, &ReportOptions::default());
+        assert!(report.contains("**Tag Filters:** `name=*contacts*`\n"));
+        assert!(report.contains("*Evaluated by k6r on the filtered points only"));
+    }
+
+    #[test]
+    fn test_parse_csv_matches_jsonl() {
+        // The CSV fixture holds the JSONL fixture's points, written with
+        // timeFormat=rfc3339 so both keep the same timestamp precision
+        let csv = include_str!("../tests/fixtures/k6-run.csv");
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+
+        let options = ParseOptions::default();
+        let from_csv = parse_csv(csv.as_bytes(), &options).unwrap();
+        let from_jsonl = parse_jsonl(jsonl.as_bytes(), &options).unwrap();
+
+        assert!(from_csv.warnings.is_empty());
+        assert_eq!(
+            from_csv.state.unwrap().test_run_duration_ms,
+            from_jsonl.state.unwrap().test_run_duration_ms
+        );
+        assert_eq!(from_csv.endpoints, from_jsonl.endpoints);
+        assert_eq!(from_csv.samples, from_jsonl.samples);
+
+        for (name, want) in &from_jsonl.metrics {
+            let got = &from_csv.metrics[name];
+            // CSV carries no metric definitions, so only built-in types are known
+            if name == "items_processed" {
+                assert_eq!(got.metric_type, MetricType::Trend);
+                continue;
+            }
+            assert_eq!(got.metric_type, want.metric_type, "{}", name);
+            assert_eq!(got.contains, want.contains, "{}", name);
+            assert_eq!(got.values, want.values, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_unix_timestamps_and_quoted_fields() {
+        let content = "\
+metric_name,timestamp,metric_value,check,method,name,status,expected_response,extra_tags,metadata
+http_req_duration,1717236000,120.5,,GET,\"https://example.com/a,b\",200,true,env=prod&team=core,
+http_req_duration,1717236002,80,,GET,\"https://example.com/a,b\",503,false,env=prod,
+http_req_duration,1717236002,oops,,GET,https://example.com/,200,true,,
+http_req_duration,1717236002,NaN,,GET,https://example.com/,200,true,,
+http_req_duration,1717236002,inf,,GET,https://example.com/,200,true,,
+checks,1717236003,1,\"status is \"\"ok\"\"\",,,,,,
+";
+        let summary = parse_csv(content.as_bytes(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(summary.state.unwrap().test_run_duration_ms, 3000.0);
+        assert_eq!(summary.samples["http_req_duration"], vec![80.0, 120.5]);
+        assert_eq!(summary.metrics["checks"].metric_type, MetricType::Rate);
+        assert_eq!(summary.warnings.len(), 4);
+        assert!(summary.warnings[1].starts_with("line 4: invalid metric_value"));
+        assert_eq!(summary.warnings[2], format!("line 5: {}", NON_FINITE_VALUE));
+
+        let endpoint = &summary.endpoints[0];
+        assert_eq!(endpoint.name, "https://example.com/a,b");
+        assert_eq!(endpoint.requests, 2);
+        assert_eq!(endpoint.failed, 1);
+        assert_eq!(endpoint.statuses["503"], 1);
+
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            parse_csv(content.as_bytes(), &strict),
+            Err(Error::Csv { line: 4, .. })
+        ));
+        assert!(matches!(
+            parse_csv("metric_name,value\n".as_bytes(), &strict),
+            Err(Error::Csv { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_split_csv_record() {
+        assert_eq!(
+            split_csv_record(r#"a,"b,c","say ""hi""",,"#).unwrap(),
+            vec!["a", "b,c", r#"say "hi""#, "", ""]
+        );
+        assert!(split_csv_record(r#"a,"b"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_timestamp_units() {
+        let ms = 1_717_236_000_000.0;
+        assert_eq!(parse_csv_timestamp("1717236000"), Some(ms));
+        assert_eq!(parse_csv_timestamp("1717236000000"), Some(ms));
+        assert_eq!(parse_csv_timestamp("1717236000000000"), Some(ms));
+        assert_eq!(parse_csv_timestamp("1717236000000000000"), Some(ms));
+        assert_eq!(parse_csv_timestamp("2024-06-01T10:00:00Z"), Some(ms));
+    }
+
+    #[test]
+    fn test_detect_format_handle_summary() {
+        let content = r#"{"metrics":{"http_reqs":{"type":"counter"}}}"#;
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::HandleSummary)
+        ));
+    }
+
+    #[test]
+    fn test_detect_format_jsonl() {
+        let content = r#"{"type":"Metric","metric":"http_reqs","data":{}}
+{"type":"Point","metric":"http_reqs","data":{"value":1}}"#;
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::Jsonl)
+        ));
+    }
+
+    #[test]
+    fn test_parse_jsonl_basic() {
+        let content = r#"{"type":"Metric","data":{"type":"trend","contains":"time","thresholds":[]},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:00.000+00:00","value":100.0,"tags":null},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":200.0,"tags":null},"metric":"http_req_duration"}"#;
+
+        let summary = parse_jsonl(content.as_bytes(), &ParseOptions::default()).unwrap();
+
+        assert!(summary.metrics.contains_key("http_req_duration"));
+        let metric = summary.metrics.get("http_req_duration").unwrap();
+        assert_eq!(metric.metric_type, MetricType::Trend);
+        assert_eq!(metric.values.get("avg"), Some(&150.0));
+        assert_eq!(metric.values.get("min"), Some(&100.0));
+        assert_eq!(metric.values.get("max"), Some(&200.0));
+    }
+
+    #[test]
+    fn test_parse_handle_summary() {
+        let content = r#"{
+            "metrics": {
+                "http_reqs": {
+                    "type": "counter",
+                    "contains": "default",
+                    "values": {"count": 100, "rate": 10.0},
+                    "thresholds": {}
+                }
+            }
+        }"#;
+
+        let summary: K6Summary = serde_json::from_str(content).unwrap();
+
+        assert!(summary.metrics.contains_key("http_reqs"));
+        let metric = summary.metrics.get("http_reqs").unwrap();
+        assert_eq!(metric.metric_type, MetricType::Counter);
+        assert_eq!(metric.values.get("count"), Some(&100.0));
+    }
+
+    #[test]
+    fn test_parse_jsonl_keeps_latency_samples() {
+        let content = r#"{"type":"Metric","data":{"type":"trend","contains":"time","thresholds":[]},"metric":"http_req_duration"}
+{"type":"Metric","data":{"type":"counter","contains":"default","thresholds":[]},"metric":"http_reqs"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:00.000+00:00","value":200.0,"tags":null},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":100.0,"tags":null},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":1,"tags":null},"metric":"http_reqs"}"#;
+
+        let summary = parse_jsonl(content.as_bytes(), &ParseOptions::default()).unwrap();
+
+        assert_eq!(
+            summary.samples.get("http_req_duration"),
+            Some(&vec![100.0, 200.0])
+        );
+        assert!(!summary.samples.contains_key("http_reqs"));
+        assert!(generate_report(&summary, &ReportOptions::default())
+            .contains("## Latency Distribution"));
+    }
+
+    #[test]
+    fn test_parse_duration_ms() {
+        assert_eq!(parse_duration_ms("30s"), Some(30_000.0));
+        assert_eq!(parse_duration_ms("1m30s"), Some(90_000.0));
+        assert_eq!(parse_duration_ms("500ms"), Some(500.0));
+        assert_eq!(parse_duration_ms("1h"), Some(3_600_000.0));
+        assert_eq!(parse_duration_ms("10"), Some(10_000.0));
+        assert_eq!(parse_duration_ms("10x"), None);
+        assert_eq!(parse_duration_ms(""), None);
+    }
+
+    #[test]
+    fn test_parse_handle_summary_options() {
+        let content = r#"{
+            "metrics": {},
+            "options": {
+                "summaryTrendStats": ["avg", "p(99.9)"],
+                "summaryTimeUnit": "ms",
+                "scenarios": {
+                    "browse": {
+                        "executor": "ramping-vus",
+                        "startVUs": 0,
+                        "stages": [{"duration": "30s", "target": 50}, {"duration": "1m", "target": 0}]
+                    },
+                    "api": {
+                        "executor": "constant-arrival-rate",
+                        "rate": 100,
+                        "timeUnit": "1s",
+                        "duration": "2m",
+                        "preAllocatedVUs": 10,
+                        "maxVUs": 40
+                    }
+                },
+                "thresholds": {
+                    "http_req_duration": ["p(95)<500", {"threshold": "p(99)<1000", "abortOnFail": true}]
+                }
+            },
+            "setup_data": {"token": "abc"}
+        }"#;
+
+        let summary: K6Summary = serde_json::from_str(content).unwrap();
+        let options = summary.options.as_ref().unwrap();
+
+        assert_eq!(options.summary_trend_stats, vec!["avg", "p(99.9)"]);
+        assert_eq!(options.scenarios.len(), 2);
+        let defs = &options.thresholds["http_req_duration"];
+        assert_eq!(defs[0].expression(), "p(95)<500");
+        assert_eq!(defs[1].expression(), "p(99)<1000");
+        assert!(summary.setup_data.is_some());
+
+        let report = generate_report(&summary, &ReportOptions::default());
+        assert!(report.contains("## Test Configuration"));
+        assert!(
+            report.contains("| api | constant-arrival-rate | 100 iters/1s (max 40 VUs) | 2m | - |")
+        );
+        assert!(report.contains("| browse | ramping-vus | 0 → 50 VUs | 1.50m | 30s → 50, 1m → 0 |"));
+        assert!(report.contains("\"token\": \"abc\""));
+    }
+
+    #[test]
+    fn test_parse_handle_summary_null_options() {
+        let content = r#"{
+            "metrics": {},
+            "options": {
+                "summaryTrendStats": null,
+                "stages": null,
+                "scenarios": {"default": {"executor": "ramping-vus", "stages": null}},
+                "thresholds": null
+            }
+        }"#;
+
+        let summary: K6Summary = serde_json::from_str(content).unwrap();
+        let options = summary.options.as_ref().unwrap();
+        assert!(options.summary_trend_stats.is_empty());
+        assert!(options.stages.is_empty());
+        assert!(options.scenarios["default"].stages.is_empty());
+        assert!(options.thresholds.is_empty());
+        assert!(generate_report(&summary, &ReportOptions::default()).contains("| default |"));
+    }
+
+    #[test]
+    fn test_parse_jsonl_evaluates_thresholds() {
+        let content = r#"{"type":"Metric","data":{"type":"trend","contains":"time","thresholds":["p(95)<150"]},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:00.000+00:00","value":100.0,"tags":null},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":200.0,"tags":null},"metric":"http_req_duration"}"#;
+
+        let options = ParseOptions {
+            thresholds: [(
+                "http_req_duration".to_string(),
+                vec!["p(99.9)<250".to_string(), "value<1".to_string()],
+            )]
+            .into_iter()
+            .collect(),
+            ..ParseOptions::default()
+        };
+        let summary = parse_jsonl(content.as_bytes(), &options).unwrap();
+        let thresholds = &summary.metrics["http_req_duration"].thresholds;
+
+        assert!(!thresholds["p(95)<150"].ok);
+        assert!(thresholds["p(99.9)<250"].ok);
+        assert!(thresholds["value<1"].unknown);
+    }
+
+    #[test]
+    fn test_parse_jsonl_evaluates_submetric_thresholds() {
+        let content = r#"{"type":"Point","data":{"time":"2024-01-01T10:00:00.000+00:00","value":100.0,"tags":{"status":"200"}},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":900.0,"tags":{"status":"200","group":"::login"}},"metric":"http_req_duration"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":50.0,"tags":{"status":"503"}},"metric":"http_req_duration"}"#;
+        let thresholds = [
+            ("http_req_duration{status:200}", "max<500"),
+            ("http_req_duration{ status: \"503\" }", "max<500"),
+            ("http_req_duration{status:200,group:::login}", "avg<1000"),
+            ("http_req_duration{status:404}", "p(95)<500"),
+        ];
+        let options = ParseOptions {
+            thresholds: thresholds
+                .iter()
+                .map(|(name, expr)| (name.to_string(), vec![expr.to_string()]))
+                .collect(),
+            ..ParseOptions::default()
+        };
+        let summary = parse_jsonl(content.as_bytes(), &options).unwrap();
+
+        let threshold = |name: &str, expr: &str| {
+            let threshold = &summary.metrics[name].thresholds[expr];
+            (threshold.ok, threshold.unknown)
+        };
+        assert_eq!(threshold(thresholds[0].0, "max<500"), (false, false));
+        assert_eq!(threshold(thresholds[1].0, "max<500"), (true, false));
+        assert_eq!(threshold(thresholds[2].0, "avg<1000"), (true, false));
+        assert_eq!(threshold(thresholds[3].0, "p(95)<500"), (false, true));
+        assert_eq!(summary.metrics[thresholds[0].0].values["min"], 100.0);
+        assert_eq!(summary.metrics[thresholds[1].0].values["max"], 50.0);
+        // Only the metric itself gets a histogram
+        assert_eq!(summary.samples.len(), 1);
+        assert_eq!(RunOverview::of(&summary).verdict, Some(Verdict::Failed));
+    }
+
+    #[test]
+    fn test_jsonl_stream_rolling_stats_match_summary() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let options = ParseOptions {
+            thresholds: BTreeMap::from([
+                (
+                    "http_req_duration".to_string(),
+                    vec!["p(95)<300".to_string(), "max<1000".to_string()],
+                ),
+                (
+                    "http_req_duration{status:200}".to_string(),
+                    vec!["max<1000".to_string()],
+                ),
+            ]),
+            ..ParseOptions::default()
+        };
+        let mut stream = JsonlStream::new(&options).with_rolling_stats(10_000.0);
+        for line in jsonl.split_inclusive('\n') {
+            stream.push_line(line.as_bytes()).unwrap();
+        }
+
+        let stats = stream.rolling_stats().unwrap();
+        assert_eq!(stats.snapshot().requests, 44.0);
+        let live: Vec<(String, bool)> = stats
+            .thresholds()
+            .into_iter()
+            .map(|(_, expr, t)| (expr.to_string(), t.ok))
+            .collect();
+
+        let summary = stream.finish().unwrap();
+        let expected = parse_jsonl(jsonl.as_bytes(), &options).unwrap();
+        assert_eq!(summary.metrics.len(), expected.metrics.len());
+        let mut thresholds = 0;
+        for (name, metric) in &summary.metrics {
+            for (expr, threshold) in &metric.thresholds {
+                let want = &expected.metrics[name].thresholds[expr];
+                assert_eq!(threshold.ok, want.ok, "{} {}", name, expr);
+                assert!(live.contains(&(expr.clone(), threshold.ok)), "{}", expr);
+                thresholds += 1;
+            }
+        }
+        assert_eq!((thresholds, live.len()), (3, 3));
+
+        // Only the running stats are kept: exact sums and bounds, percentiles
+        // from a histogram rather than interpolated between samples
+        let (got, want) = (
+            &summary.metrics["http_reqs"],
+            &expected.metrics["http_reqs"],
+        );
+        assert_eq!(got.values, want.values);
+        let (got, want) = (
+            &summary.metrics["http_req_duration"],
+            &expected.metrics["http_req_duration"],
+        );
+        assert_eq!(got.contains, "time");
+        let stats = |metric: &Metric| {
+            let stats = metric.values.keys().cloned();
+            stats.collect::<std::collections::BTreeSet<_>>()
+        };
+        assert_eq!(stats(got), stats(want));
+        for stat in ["avg", "min", "max"] {
+            let (got, want) = (got.values[stat], want.values[stat]);
+            assert!((got - want).abs() < 1e-9, "{}: {} != {}", stat, got, want);
+        }
+        assert!(summary.samples.is_empty() && summary.endpoints.is_empty());
+        assert!(summary.timeline.is_empty());
+        let duration = |summary: &K6Summary| summary.state.as_ref().unwrap().test_run_duration_ms;
+        assert_eq!(duration(&summary), duration(&expected));
+
+        let options = ParseOptions {
+            window: WindowOptions::new(None, None, true),
+            ..ParseOptions::default()
+        };
+        let mut stream = JsonlStream::new(&options).with_rolling_stats(10_000.0);
+        stream
+            .push_line(jsonl.lines().next().unwrap().as_bytes())
+            .unwrap();
+        assert!(matches!(stream.finish(), Err(Error::Window(_))));
+    }
+
+    #[test]
+    fn test_parse_script_options() {
+        let script = r#"
+import http from 'k6/http';
+
+// Load profile
+export const options = {
+  scenarios: {
+    browse: {
+      executor: 'ramping-vus',
+      startVUs: 0,
+      stages: [
+        { duration: '30s', target: 50 }, // ramp up
+        { duration: `1m`, target: 50 },
+        { duration: "30s", target: 0 },
+      ],
+      gracefulRampDown: '0s',
+    },
+    "api-load": {
+      executor: 'constant-arrival-rate',
+      rate: 1_000,
+      timeUnit: '1s',
+      duration: __ENV.DURATION || '2m',
+      preAllocatedVUs: 10 * 2,
+      maxVUs: 100,
+    },
+  },
+  thresholds: {
+    http_req_duration: ['p(95)<500', { threshold: 'p(99)<1000', abortOnFail: true }],
+    'http_req_failed': ["rate<0.01"],
+  },
+  tags: { env: `${__ENV.ENV}` },
+  summaryTrendStats: ['avg', 'p(99.9)'],
+};
+
+export default function () {
+  http.get('https://test.k6.io');
+}
+"#;
+
+        let options = parse_script_options(script).unwrap();
+        let browse = &options.scenarios["browse"];
+        assert_eq!(browse.executor, "ramping-vus");
+        assert_eq!(browse.start_vus, Some(0));
+        assert_eq!(browse.stages.len(), 3);
+        assert_eq!(browse.stages[1].duration, "1m");
+
+        let api = &options.scenarios["api-load"];
+        assert_eq!(api.rate, Some(1000.0));
+        assert_eq!(api.duration, None);
+        assert_eq!(api.pre_allocated_vus, None);
+        assert_eq!(api.max_vus, Some(100));
+
+        assert_eq!(options.thresholds["http_req_duration"].len(), 2);
+        assert_eq!(
+            options.thresholds["http_req_failed"][0].expression(),
+            "rate<0.01"
+        );
+        assert_eq!(options.summary_trend_stats, vec!["avg", "p(99.9)"]);
+    }
+
+    #[test]
+    fn test_parse_script_options_shortcuts() {
+        let script = "const options = { vus: 10, duration: '30s' };\nexport { options };";
+        let options = parse_script_options(script).unwrap();
+        assert_eq!(options.vus, Some(10));
+        assert_eq!(options.duration.as_deref(), Some("30s"));
+    }
+
+    #[test]
+    fn test_parse_script_options_skips_comments_and_strings() {
+        let script = r#"
+// export const options = { vus: 1 };
+/* export const options = { vus: 2 }; */
+const help = "export const options = { vus: 3 }";
+export const options = { vus: 4, duration: '1m' };
+"#;
+        let options = parse_script_options(script).unwrap();
+        assert_eq!(options.vus, Some(4));
+        assert_eq!(options.duration.as_deref(), Some("1m"));
+    }
+
+    #[test]
+    fn test_parse_script_options_errors() {
+        assert!(parse_script_options("export default function () {}").is_err());
+        let err = parse_script_options("export const options = {\n  vus: 'ten,\n};").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+
+    #[test]
+    fn test_detect_format_errors() {
+        assert!(matches!(
+            detect_format("  \n").map(|d| d.format),
+            Err(Error::Format(_))
+        ));
+        assert!(matches!(
+            detect_format("time,metric,value").map(|d| d.format),
+            Err(Error::Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_detect_format_summary_export() {
+        let content = r#"{"metrics":{"http_reqs":{"count":10,"rate":2.5}},"root_group":{}}"#;
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::SummaryExport)
+        ));
+
+        // The first line of a pretty-printed summary doesn't tell the kinds apart
+        let content = include_str!("../tests/fixtures/k6-summary-export.json");
+        let head = content.lines().next().unwrap();
+        assert!(matches!(
+            detect_format(head).map(|d| d.format),
+            Ok(FileFormat::HandleSummary)
+        ));
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::SummaryExport)
+        ));
+
+        let content = include_str!("../tests/fixtures/k6-run-summary.json");
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::HandleSummary)
+        ));
+    }
+
+    #[test]
+    fn test_detect_format_csv() {
+        let content = "metric_name,timestamp,metric_value,check,error,extra_tags,metadata\n";
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::Csv)
+        ));
+    }
+
+    #[test]
+    fn test_parse_jsonl_reports_skipped_lines() {
+        let content = r#"{"type":"Metric","data":{"type":"counter","contains":"default","thresholds":[]},"metric":"http_reqs"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:00.000+00:00","value":1,"tags":null},"metric":"http_reqs"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","val
+{"type":"Point","data":{"time":"2024-01-01T10:00:01.000+00:00","value":1,"tags":null},"metric":"http_reqs"}"#;
+
+        let summary = parse_jsonl(content.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(summary.metrics["http_reqs"].values.get("count"), Some(&2.0));
+        assert_eq!(summary.warnings.len(), 2);
+        assert!(summary.warnings[0].starts_with("Skipped 1 malformed line(s) of 4"));
+        assert!(summary.warnings[1].starts_with("line 3: "));
+
+        let report = generate_report(&summary, &ReportOptions::default());
+        assert!(report.contains("> **Warning:** Skipped 1 malformed line(s)"));
+
+        let strict = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        assert!(matches!(
+            parse_jsonl(content.as_bytes(), &strict),
+            Err(Error::Jsonl { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_jsonl_limits_reported_lines() {
+        let mut content = String::from(
+            r#"{"type":"Point","data":{"time":"2024-01-01T10:00:00.000+00:00","value":1,"tags":null},"metric":"vus"}"#,
+        );
+        for _ in 0..8 {
+            content.push_str("\nnot json");
+        }
+
+        let summary = parse_jsonl(content.as_bytes(), &ParseOptions::default()).unwrap();
+        assert_eq!(summary.warnings.len(), 1 + MAX_REPORTED_LINE_ERRORS);
+        assert!(summary.warnings[0].starts_with("Skipped 8 malformed line(s) of 9"));
+    }
+
+    #[test]
+    fn test_parse_jsonl_without_valid_lines() {
+        let err = parse_jsonl("{\"truncated\n".as_bytes(), &ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, Error::Jsonl { line: 1, .. }));
+    }
+
+    #[test]
+    fn test_detect_format_pretty_handle_summary() {
+        let content = "{\n  \"metrics\": {\n    \"http_reqs\": {\"type\": \"counter\"}\n  }\n}\n";
+        assert!(matches!(
+            detect_format(content).map(|d| d.format),
+            Ok(FileFormat::HandleSummary)
+        ));
+        // Only the head of a stream is available when detecting
+        assert!(matches!(
+            detect_format("\n{\n").map(|d| d.format),
+            Ok(FileFormat::HandleSummary)
+        ));
+    }
+
+    #[test]
+    fn test_detect_format_confidence() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let detection = detect_format(jsonl).unwrap();
+        assert_eq!(detection.confidence, Some(Confidence::High));
+        assert!(detection.alternatives.is_empty());
+
+        let detection = detect_format("{\"foo\":1}\n").unwrap();
+        assert_eq!(detection.format, FileFormat::Jsonl);
+        assert_eq!(detection.confidence, Some(Confidence::Medium));
+        assert_eq!(
+            detection.alternatives,
+            vec![(FileFormat::HandleSummary, Confidence::Low)]
+        );
+
+        let detection = detect_format("{\n  \"metrics\": {\n").unwrap();
+        assert_eq!(detection.format, FileFormat::HandleSummary);
+        assert_eq!(detection.confidence, Some(Confidence::Medium));
+        assert_eq!(
+            detection.alternatives[0],
+            (FileFormat::SummaryExport, Confidence::Low)
+        );
+    }
+
+    #[test]
+    fn test_read_head_stops_when_certain() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let mut reader = std::io::Cursor::new(jsonl);
+        let (head, detection) = read_head(&mut reader).unwrap();
+        assert_eq!(detection.format, FileFormat::Jsonl);
+        assert_eq!(head, jsonl.lines().next().unwrap().to_string() + "\n");
+
+        let export = include_str!("../tests/fixtures/k6-summary-export.json");
+        let mut reader = std::io::Cursor::new(export);
+        let (head, detection) = read_head(&mut reader).unwrap();
+        assert_eq!(detection.format, FileFormat::SummaryExport);
+        assert!(head.len() < export.len());
+    }
+
+    #[test]
+    fn test_load_input_with_given_format() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/k6-run.csv");
+        let options = ParseOptions::default();
+
+        let (detection, summary) = load_input(&path, Some(FileFormat::Csv), &options).unwrap();
+        assert_eq!(detection.confidence, None);
+        assert!(summary.metrics.contains_key("http_reqs"));
+
+        let err = load_input(&path, Some(FileFormat::Jsonl), &options);
+        assert!(matches!(err, Err(Error::Jsonl { line: 1, .. })));
+    }
+
+    fn decompress_to_string(bytes: Vec<u8>) -> String {
+        let mut reader = decompress(std::io::Cursor::new(bytes)).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn test_decompress_gzip() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(jsonl.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(decompress_to_string(compressed), jsonl);
+    }
+
+    #[test]
+    fn test_decompress_zstd_multiple_frames() {
+        use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+
+        let first = r#"{"type":"Metric","data":{"type":"gauge","contains":"default","thresholds":[]},"metric":"vus"}
+{"type":"Point","data":{"time":"2024-01-01T10:00:00Z","value":1},"metric":"vus"}"#;
+        let second = "\n{\"type\":\"Point\",\"data\":{\"time\":\"2024-01-01T10:00:01Z\",\"value\":2},\"metric\":\"vus\"}\n";
+        let mut compressed = compress_to_vec(first.as_bytes(), CompressionLevel::Fastest);
+        compressed.extend(compress_to_vec(
+            second.as_bytes(),
+            CompressionLevel::Fastest,
+        ));
+
+        let content = decompress_to_string(compressed);
+        assert_eq!(content, format!("{}{}", first, second));
+
+        let mut reader = decompress(std::io::Cursor::new(content.into_bytes())).unwrap();
+        let (head, detection) = read_head(&mut reader).unwrap();
+        assert_eq!(detection.format, FileFormat::Jsonl);
+        let summary = parse_jsonl(
+            std::io::Cursor::new(head).chain(reader),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(summary.metrics["vus"].values.get("value"), Some(&2.0));
+    }
+
+    #[test]
+    fn test_decompress_plain_passthrough() {
+        let content = "{\"metrics\":{}}";
+        assert_eq!(decompress_to_string(content.as_bytes().to_vec()), content);
+    }
+
+    #[test]
+    fn test_decompress_truncated_gzip() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, jsonl.as_bytes()).unwrap();
+        let mut compressed = encoder.finish().unwrap();
+        compressed.truncate(compressed.len() / 2);
+
+        let reader = decompress(std::io::Cursor::new(compressed)).unwrap();
+        assert!(matches!(
+            parse_jsonl(reader, &ParseOptions::default()),
+            Err(Error::Input(_))
+        ));
+    }
+}
@@ -0,0 +1,2068 @@
+//! Turning a summary into a report. Sections build a [`Document`] that any
+//! [`Renderer`] can write out; a MiniJinja template can replace the layout.
+
+use crate::analyze::{
+    default_trend_stats, format_offset, glob_match, latency_metric_names, log_histogram,
+    sort_stats, value_at_percentile, LiveSnapshot, RollingStats, RunOverview, Verdict,
+    SPECTRUM_PERCENTILES,
+};
+use crate::parse::parse_duration_ms;
+use crate::{
+    Check, EndpointStats, Error, Group, K6Summary, Metric, MetricType, Scenario, Section, Stage,
+    TestOptions, Threshold, TimelineBucket, TrendStat, Unit,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use std::str::FromStr;
+
+pub mod document;
+mod html;
+mod markdown;
+mod text;
+
+use document::{BarChart, Block, Document, KeyValueList, Sparkline, Status, Table, Text};
+pub use html::Html;
+pub use markdown::Markdown;
+pub use text::{PlainText, Terminal};
+
+// =============================================================================
+// Test Configuration
+// =============================================================================
+
+/// Scenarios from the options, or the implicit `default` scenario k6 derives
+/// from the `vus`/`duration`/`iterations`/`stages` shortcuts.
+fn effective_scenarios(options: &TestOptions) -> Vec<(String, Scenario)> {
+    if !options.scenarios.is_empty() {
+        return options
+            .scenarios
+            .iter()
+            .map(|(name, scenario)| (name.clone(), scenario.clone()))
+            .collect();
+    }
+
+    let executor = if !options.stages.is_empty() {
+        "ramping-vus"
+    } else if options.iterations.is_some() && options.duration.is_none() {
+        "shared-iterations"
+    } else if options.vus.is_some() || options.duration.is_some() {
+        "constant-vus"
+    } else {
+        return Vec::new();
+    };
+
+    vec![(
+        "default".to_string(),
+        Scenario {
+            executor: executor.to_string(),
+            vus: options.vus,
+            iterations: options.iterations,
+            duration: options.duration.clone(),
+            start_vus: options.vus,
+            stages: options.stages.clone(),
+            ..Default::default()
+        },
+    )]
+}
+
+fn describe_load(scenario: &Scenario) -> String {
+    let peak = |start: f64| {
+        scenario
+            .stages
+            .iter()
+            .map(|s| s.target)
+            .fold(start, f64::max)
+    };
+    let per = scenario.time_unit.as_deref().unwrap_or("1s");
+    let max_vus = scenario
+        .max_vus
+        .map(|v| format!(" (max {} VUs)", v))
+        .unwrap_or_default();
+
+    match scenario.executor.as_str() {
+        "ramping-vus" => {
+            let start = scenario.start_vus.unwrap_or(1) as f64;
+            format!("{} → {} VUs", start, peak(start))
+        }
+        "constant-arrival-rate" => {
+            format!("{} iters/{}{}", scenario.rate.unwrap_or(0.0), per, max_vus)
+        }
+        "ramping-arrival-rate" => {
+            let start = scenario.start_rate.unwrap_or(0.0);
+            format!("{} → {} iters/{}{}", start, peak(start), per, max_vus)
+        }
+        "per-vu-iterations" => format!(
+            "{} VUs × {} iters",
+            scenario.vus.unwrap_or(1),
+            scenario.iterations.unwrap_or(1)
+        ),
+        "shared-iterations" => format!(
+            "{} VUs, {} iters",
+            scenario.vus.unwrap_or(1),
+            scenario.iterations.unwrap_or(1)
+        ),
+        _ => match scenario.vus {
+            Some(vus) => format!("{} VUs{}", vus, max_vus),
+            None => "-".to_string(),
+        },
+    }
+}
+
+fn describe_duration(scenario: &Scenario, units: &Units) -> String {
+    if let Some(duration) = &scenario.duration {
+        return duration.clone();
+    }
+    if scenario.stages.is_empty() {
+        return "-".to_string();
+    }
+    let total: Option<f64> = scenario
+        .stages
+        .iter()
+        .map(|s| parse_duration_ms(&s.duration))
+        .sum();
+    match total {
+        Some(ms) => units.elapsed(ms),
+        None => "-".to_string(),
+    }
+}
+
+fn describe_stages(stages: &[Stage]) -> String {
+    if stages.is_empty() {
+        return "-".to_string();
+    }
+    stages
+        .iter()
+        .map(|s| format!("{} → {}", s.duration, s.target))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// =============================================================================
+// Formatting Utilities
+// =============================================================================
+
+/// Display unit for durations; `Auto` picks the most readable one per value.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum TimeUnit {
+    /// The most readable unit per value
+    #[default]
+    Auto,
+    /// Microseconds
+    Us,
+    /// Milliseconds
+    Ms,
+    /// Seconds
+    S,
+    /// Minutes
+    M,
+}
+
+/// Formats metric values according to their unit and the configured precision.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Units {
+    /// Unit of durations
+    pub time_unit: TimeUnit,
+    /// Decimal places
+    pub precision: usize,
+    /// Units declared for custom metrics, overriding their `contains` type
+    pub metric_units: HashMap<String, Unit>,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Units {
+            time_unit: TimeUnit::Auto,
+            precision: 2,
+            metric_units: HashMap::new(),
+        }
+    }
+}
+
+impl Units {
+    fn unit_for(&self, name: &str, contains: &str) -> Unit {
+        if let Some(unit) = self.metric_units.get(name) {
+            return *unit;
+        }
+        match contains {
+            "time" => Unit::Milliseconds,
+            "data" => Unit::Bytes,
+            _ => Unit::Plain,
+        }
+    }
+
+    fn number(&self, value: f64) -> String {
+        format!("{:.*}", self.precision, value)
+    }
+
+    fn duration(&self, ms: f64) -> String {
+        let p = self.precision;
+        match self.time_unit {
+            TimeUnit::Auto => self.elapsed(ms),
+            TimeUnit::Us => format!("{:.*}µs", p, ms * 1000.0),
+            TimeUnit::Ms => format!("{:.*}ms", p, ms),
+            TimeUnit::S => format!("{:.*}s", p, ms / 1000.0),
+            TimeUnit::M => format!("{:.*}m", p, ms / 60_000.0),
+        }
+    }
+
+    /// Always auto-scaled, for wall-clock spans such as the test duration
+    fn elapsed(&self, ms: f64) -> String {
+        let p = self.precision;
+        if ms >= 60_000.0 {
+            format!("{:.*}m", p, ms / 60_000.0)
+        } else if ms >= 1000.0 {
+            format!("{:.*}s", p, ms / 1000.0)
+        } else if ms >= 1.0 {
+            format!("{:.*}ms", p, ms)
+        } else {
+            format!("{:.*}µs", p, ms * 1000.0)
+        }
+    }
+
+    fn count(&self, count: f64) -> String {
+        let count = count as u64;
+        if count >= 1_000_000 {
+            format!("{:.*}M", self.precision, count as f64 / 1_000_000.0)
+        } else if count >= 1_000 {
+            format!("{:.*}K", self.precision, count as f64 / 1_000.0)
+        } else {
+            format!("{}", count)
+        }
+    }
+
+    fn bytes(&self, bytes: f64) -> String {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        let mut value = bytes;
+        let mut idx = 0;
+        while value.abs() >= 1024.0 && idx < UNITS.len() - 1 {
+            value /= 1024.0;
+            idx += 1;
+        }
+        if idx == 0 {
+            format!("{}{}", value as u64, UNITS[0])
+        } else {
+            format!("{:.*}{}", self.precision, value, UNITS[idx])
+        }
+    }
+
+    fn rate(&self, rate: f64) -> String {
+        format!("{:.*}/s", self.precision, rate)
+    }
+
+    fn percent(&self, rate: f64) -> String {
+        format!("{:.*}%", self.precision, rate * 100.0)
+    }
+
+    /// Formats a raw value in the given unit
+    fn amount(&self, value: f64, unit: Unit) -> String {
+        match unit {
+            Unit::Milliseconds => self.duration(value),
+            Unit::Seconds => self.duration(value * 1000.0),
+            Unit::Bytes => self.bytes(value),
+            Unit::Plain => self.number(value),
+        }
+    }
+
+    /// Formats a per-second rate of a raw value in the given unit
+    fn amount_rate(&self, rate: f64, unit: Unit) -> String {
+        match unit {
+            Unit::Bytes => format!("{}/s", self.bytes(rate)),
+            Unit::Milliseconds | Unit::Seconds => format!("{}/s", self.amount(rate, unit)),
+            Unit::Plain => self.rate(rate),
+        }
+    }
+
+    fn value(&self, value: f64, key: &str, name: &str, metric: &Metric) -> String {
+        let unit = self.unit_for(name, &metric.contains);
+        match (key, metric.metric_type) {
+            ("passes" | "fails", _) | ("count", MetricType::Trend | MetricType::Rate) => {
+                self.count(value)
+            }
+            ("count", _) if unit == Unit::Plain => self.count(value),
+            ("rate", MetricType::Rate) => self.percent(value),
+            ("rate", MetricType::Counter) => self.amount_rate(value, unit),
+            _ => self.amount(value, unit),
+        }
+    }
+}
+
+// =============================================================================
+// Metric Selection
+// =============================================================================
+
+/// A `--route` section: metrics matching any of the glob patterns are listed
+/// under their own heading instead of the HTTP and All Metrics sections
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct MetricRoute {
+    /// Heading of the section
+    pub heading: String,
+    /// Glob patterns of the metrics listed
+    #[serde(rename = "metrics")]
+    pub patterns: Vec<String>,
+    /// Stat keys to show as columns; empty means every stat the metrics have
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+impl FromStr for MetricRoute {
+    type Err = String;
+
+    /// Parses `Heading=pattern,pattern[:column,column]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((heading, rest)) = s.split_once('=') else {
+            return Err(format!("expected HEADING=PATTERNS[:COLUMNS], got '{}'", s));
+        };
+        let (patterns, columns) = rest.split_once(':').unwrap_or((rest, ""));
+        let list = |s: &str| -> Vec<String> {
+            s.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        let route = MetricRoute {
+            heading: heading.trim().to_string(),
+            patterns: list(patterns),
+            columns: list(columns),
+        };
+        if route.heading.is_empty() || route.patterns.is_empty() {
+            return Err(format!(
+                "a route needs a heading and at least one pattern: '{}'",
+                s
+            ));
+        }
+        Ok(route)
+    }
+}
+
+// =============================================================================
+// Report Generation
+// =============================================================================
+
+impl Section {
+    /// The section's content, empty when the summary has nothing to show
+    pub fn build(self, summary: &K6Summary, options: &ReportOptions) -> Document {
+        match self {
+            Section::Summary => generate_summary_section(summary, options),
+            Section::Configuration => generate_configuration_section(summary, options),
+            Section::Thresholds => generate_thresholds_section(summary),
+            Section::Http => generate_http_metrics_section(summary, options),
+            Section::Endpoints => generate_endpoints_section(summary, options),
+            Section::Timeline => generate_timeline_section(summary, options),
+            Section::Latency => generate_latency_distribution_section(summary, options),
+            Section::Checks => generate_checks_section(summary, options),
+            Section::Routes => {
+                let mut document = Document::new();
+                for route in &options.routes {
+                    document.append(generate_route_section(summary, route, options));
+                }
+                document
+            }
+            Section::Metrics => generate_all_metrics_section(summary, options),
+        }
+    }
+}
+
+/// What a report shows and how values are formatted
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct ReportOptions {
+    /// Top-level heading
+    pub title: String,
+    /// Key/value pairs shown in a table under the title
+    pub meta: Vec<(String, String)>,
+    /// Built-in sections to show, in order
+    pub sections: Vec<Section>,
+    /// Stats shown for trend metrics
+    pub trend_stats: Vec<TrendStat>,
+    /// How values are formatted
+    pub units: Units,
+    /// Glob patterns of metrics to list; empty lists all
+    pub include: Vec<String>,
+    /// Glob patterns of metrics to leave out, overriding `include`
+    pub exclude: Vec<String>,
+    /// `--route` sections, in order; the first matching route claims a metric
+    pub routes: Vec<MetricRoute>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            title: DEFAULT_TITLE.to_string(),
+            meta: Vec::new(),
+            sections: Section::ALL.to_vec(),
+            trend_stats: default_trend_stats(),
+            units: Units::default(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl ReportOptions {
+    /// Whether a metric is listed in the metric tables at all
+    fn shows(&self, name: &str) -> bool {
+        !name.contains('{')
+            && (self.include.is_empty() || self.include.iter().any(|p| glob_match(p, name)))
+            && !self.exclude.iter().any(|p| glob_match(p, name))
+    }
+
+    /// The first route claiming the metric
+    fn route_for(&self, name: &str) -> Option<&MetricRoute> {
+        self.routes
+            .iter()
+            .find(|route| route.patterns.iter().any(|p| glob_match(p, name)))
+    }
+
+    /// Listed in one of the built-in sections rather than a route
+    fn shows_unrouted(&self, name: &str) -> bool {
+        self.shows(name) && self.route_for(name).is_none()
+    }
+}
+
+/// Title of a report unless one is configured
+pub const DEFAULT_TITLE: &str = "K6 Load Test Report";
+
+/// Renders the built-in Markdown report
+pub fn generate_report(summary: &K6Summary, options: &ReportOptions) -> String {
+    render_template(DEFAULT_TEMPLATE_NAME, DEFAULT_TEMPLATE, summary, options)
+        .expect("the built-in template renders")
+}
+
+/// Builds the full report, the same layout as the built-in template, for any
+/// [`Renderer`]
+pub fn build_document(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let mut document = Document::new();
+    document.heading(1, options.title.as_str());
+
+    if !options.meta.is_empty() {
+        let mut meta = KeyValueList::with_headers("Key", "Value");
+        for (key, value) in &options.meta {
+            meta.row(key.as_str(), value.as_str());
+        }
+        document.push(meta);
+    }
+
+    let mut run = KeyValueList::new();
+    if let Some(state) = &summary.state {
+        run.row(
+            "Test Duration",
+            options.units.elapsed(state.test_run_duration_ms),
+        );
+    }
+    if let Some(window) = &summary.window {
+        let mut text = format!(
+            "{} – {} of the {} run",
+            format_offset(window.start_ms),
+            format_offset(window.end_ms),
+            format_offset(window.run_ms)
+        );
+        if let Some(vus) = window.steady_vus {
+            text.push_str(&format!(" (steady state at {} VUs)", vus as i64));
+        }
+        run.row("Time Window", text);
+    }
+    if !summary.filters.is_empty() {
+        let mut text = Text::default();
+        for (idx, filter) in summary.filters.iter().enumerate() {
+            if idx > 0 {
+                text = text.then(", ");
+            }
+            text = text.then(Text::code(filter.to_string()));
+        }
+        run.row("Tag Filters", text);
+    }
+    if !run.rows.is_empty() {
+        document.push(run);
+    }
+
+    if !summary.warnings.is_empty() {
+        let lines = summary.warnings.iter().enumerate().map(|(idx, warning)| {
+            if idx == 0 {
+                Text::strong("Warning:").then(format!(" {}", warning))
+            } else {
+                Text::plain(warning.as_str())
+            }
+        });
+        document.push(Block::Quote(lines.collect()));
+    }
+    document.rule();
+
+    for section in &options.sections {
+        document.append(section.build(summary, options));
+    }
+    document
+}
+
+fn generate_summary_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let units = &options.units;
+    let mut list = KeyValueList::with_headers("Metric", "Value");
+
+    if let Some(metric) = summary.metrics.get("http_reqs") {
+        if let Some(count) = metric.values.get("count") {
+            list.row("Total Requests", units.count(*count));
+        }
+        if let Some(rate) = metric.values.get("rate") {
+            list.row("Request Rate", units.rate(*rate));
+        }
+    }
+
+    if let Some(metric) = summary.metrics.get("http_req_failed") {
+        if let Some(fails) = metric.values.get("fails") {
+            let rate = metric.values.get("rate").copied().unwrap_or(0.0);
+            list.row(
+                "Failed Requests",
+                format!("{} ({})", units.count(*fails), units.percent(rate)),
+            );
+        }
+    }
+
+    if let Some(metric) = summary.metrics.get("http_req_duration") {
+        if let Some(avg) = metric.values.get("avg") {
+            list.row("Avg Response Time", units.duration(*avg));
+        }
+        if let Some(p95) = metric.values.get("p(95)") {
+            list.row("P95 Response Time", units.duration(*p95));
+        }
+    }
+
+    if let Some(metric) = summary.metrics.get("iterations") {
+        if let Some(count) = metric.values.get("count") {
+            list.row("Iterations", units.count(*count));
+        }
+    }
+
+    for (name, label) in [
+        ("data_received", "Data Received"),
+        ("data_sent", "Data Sent"),
+    ] {
+        if let Some(metric) = summary.metrics.get(name) {
+            if let Some(count) = metric.values.get("count") {
+                let rate = metric.values.get("rate").copied().unwrap_or(0.0);
+                list.row(
+                    label,
+                    format!(
+                        "{} ({})",
+                        units.value(*count, "count", name, metric),
+                        units.value(rate, "rate", name, metric)
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Some(metric) = summary.metrics.get("vus") {
+        if let Some(value) = metric.values.get("value") {
+            list.row("Virtual Users", format!("{}", *value as u64));
+        }
+    }
+
+    let mut document = Document::new();
+    document.heading(2, "Summary");
+    document.push(list);
+    document.rule();
+    document
+}
+
+fn generate_configuration_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let scenarios = summary
+        .options
+        .as_ref()
+        .map(effective_scenarios)
+        .unwrap_or_default();
+    let setup_data = summary.setup_data.as_ref().filter(|v| !v.is_null());
+
+    let mut document = Document::new();
+    if scenarios.is_empty() && setup_data.is_none() {
+        return document;
+    }
+
+    document.heading(2, "Test Configuration");
+
+    if !scenarios.is_empty() {
+        let mut table = Table::new(["Scenario", "Executor", "VUs / Rate", "Duration", "Stages"]);
+        for (name, scenario) in &scenarios {
+            let executor = if scenario.executor.is_empty() {
+                "-"
+            } else {
+                &scenario.executor
+            };
+            table.row([
+                name.clone(),
+                executor.to_string(),
+                describe_load(scenario),
+                describe_duration(scenario, &options.units),
+                describe_stages(&scenario.stages),
+            ]);
+        }
+        document.push(table);
+    }
+
+    if let Some(data) = setup_data {
+        const MAX_SETUP_DATA_LEN: usize = 2000;
+        let mut json = serde_json::to_string_pretty(data).unwrap_or_default();
+        if json.len() > MAX_SETUP_DATA_LEN {
+            let cut = (0..=MAX_SETUP_DATA_LEN)
+                .rev()
+                .find(|&i| json.is_char_boundary(i))
+                .unwrap_or(0);
+            json.truncate(cut);
+            json.push_str("\n…");
+        }
+        document.paragraph(Text::strong("Setup data:"));
+        document.push(Block::Code {
+            language: "json".to_string(),
+            text: json,
+        });
+    }
+
+    document.rule();
+    document
+}
+
+fn generate_thresholds_section(summary: &K6Summary) -> Document {
+    let mut thresholds: Vec<(String, String, &Threshold)> = Vec::new();
+
+    for (metric_name, metric) in &summary.metrics {
+        for (threshold_expr, result) in &metric.thresholds {
+            thresholds.push((metric_name.clone(), threshold_expr.clone(), result));
+        }
+    }
+
+    let mut document = Document::new();
+    if thresholds.is_empty() {
+        return document;
+    }
+
+    document.heading(2, "Thresholds");
+    if !summary.filters.is_empty() {
+        document.paragraph(Text::emphasis(
+            "Evaluated by k6r on the filtered points only, not k6's results for the whole run.",
+        ));
+    }
+
+    // Failures first, then thresholds that could not be evaluated
+    let rank = |t: &Threshold| match (t.ok, t.unknown) {
+        (_, true) => 1,
+        (false, false) => 0,
+        (true, false) => 2,
+    };
+    thresholds.sort_by(|a, b| rank(a.2).cmp(&rank(b.2)).then_with(|| a.0.cmp(&b.0)));
+
+    let mut table = Table::new(["Metric", "Threshold", "Status"]);
+    for (metric_name, threshold_expr, result) in thresholds {
+        table.row([
+            Text::plain(metric_name),
+            Text::code(threshold_expr),
+            threshold_status(result),
+        ]);
+    }
+    document.push(table);
+
+    document.rule();
+    document
+}
+
+fn threshold_status(threshold: &Threshold) -> Text {
+    match (threshold.ok, threshold.unknown) {
+        (_, true) => Text::status(Status::Unknown, "? N/A"),
+        (true, false) => Text::status(Status::Pass, "✓ PASS"),
+        (false, false) => Text::status(Status::Fail, Text::plain("✗ ").then(Text::strong("FAIL"))),
+    }
+}
+
+fn generate_http_metrics_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let mut http_metrics: Vec<(&String, &Metric)> = summary
+        .metrics
+        .iter()
+        .filter(|(name, _)| name.starts_with("http_") && options.shows_unrouted(name))
+        .collect();
+
+    let mut document = Document::new();
+    if http_metrics.is_empty() {
+        return document;
+    }
+
+    document.heading(2, "HTTP Metrics");
+    http_metrics.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, metric) in http_metrics {
+        document.heading(
+            3,
+            format!(
+                "{} ({})",
+                name,
+                format!("{:?}", metric.metric_type).to_lowercase()
+            ),
+        );
+        document.push(stat_list(name, metric, options));
+    }
+
+    document.rule();
+    document
+}
+
+/// A metric's values as a Stat/Value list, trend stats in the configured order
+fn stat_list(name: &str, metric: &Metric, options: &ReportOptions) -> KeyValueList {
+    let mut list = KeyValueList::with_headers("Stat", "Value");
+    for (key, value) in sort_stats(&metric.values, &options.trend_stats) {
+        list.row(key.clone(), options.units.value(*value, key, name, metric));
+    }
+    list
+}
+
+/// Endpoints beyond this many rows are summarized in a single line
+const MAX_ENDPOINT_ROWS: usize = 25;
+
+fn generate_endpoints_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let mut document = Document::new();
+    if summary.endpoints.is_empty() {
+        return document;
+    }
+    let units = &options.units;
+
+    document.heading(2, "HTTP Endpoints");
+    let mut table = Table::new([
+        "Endpoint",
+        "Requests",
+        "Failed",
+        "Avg",
+        "P95",
+        "Status Codes",
+    ]);
+
+    for endpoint in summary.endpoints.iter().take(MAX_ENDPOINT_ROWS) {
+        let label = format!("{} {}", endpoint.method, endpoint.name);
+        let statuses: Vec<String> = endpoint
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{}: {}", status, count))
+            .collect();
+        table.row([
+            label.trim().to_string(),
+            endpoint.requests.to_string(),
+            endpoint.failed.to_string(),
+            units.duration(endpoint.avg),
+            units.duration(endpoint.p95),
+            statuses.join(", "),
+        ]);
+    }
+    document.push(table);
+
+    if summary.endpoints.len() > MAX_ENDPOINT_ROWS {
+        document.paragraph(Text::emphasis(format!(
+            "{} more endpoints not shown.",
+            summary.endpoints.len() - MAX_ENDPOINT_ROWS
+        )));
+    }
+
+    document.rule();
+    document
+}
+
+fn generate_timeline_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let mut document = Document::new();
+    if summary.timeline.is_empty() {
+        return document;
+    }
+    let units = &options.units;
+    let optional = |value: Option<f64>, format: &dyn Fn(f64) -> String| {
+        value.map_or_else(|| "-".to_string(), format)
+    };
+
+    document.heading(2, "Timeline");
+
+    let rates: Vec<Option<f64>> = summary
+        .timeline
+        .iter()
+        .map(|bucket| {
+            (bucket.duration_ms > 0.0).then(|| bucket.requests / bucket.duration_ms * 1000.0)
+        })
+        .collect();
+    let series = |values: fn(&TimelineBucket) -> Option<f64>| -> Vec<Option<f64>> {
+        summary.timeline.iter().map(values).collect()
+    };
+    let sparklines = [
+        sparkline("VUs", series(|b| b.vus), |vus| format!("{}", vus)),
+        sparkline("Requests/s", rates.clone(), |rate| units.number(rate)),
+        sparkline("Avg", series(|b| b.avg), |ms| units.duration(ms)),
+        sparkline("P95", series(|b| b.p95), |ms| units.duration(ms)),
+        sparkline("Errors", series(|b| b.error_rate), |rate| {
+            units.percent(rate)
+        }),
+    ];
+    document.push(Block::Sparklines(
+        sparklines.into_iter().flatten().collect(),
+    ));
+
+    let mut table = Table::new(["Time", "VUs", "Requests/s", "Avg", "P95", "Errors"]);
+
+    for (bucket, rate) in summary.timeline.iter().zip(rates) {
+        table.row([
+            format_offset(bucket.start_ms),
+            optional(bucket.vus, &|vus| format!("{}", vus)),
+            optional(rate, &|rate| units.number(rate)),
+            optional(bucket.avg, &|ms| units.duration(ms)),
+            optional(bucket.p95, &|ms| units.duration(ms)),
+            optional(bucket.error_rate, &|rate| units.percent(rate)),
+        ]);
+    }
+    document.push(table);
+
+    document.rule();
+    document
+}
+
+/// A timeline series noted with its range, if it has any values
+fn sparkline(
+    label: &str,
+    values: Vec<Option<f64>>,
+    format: impl Fn(f64) -> String,
+) -> Option<Sparkline> {
+    let min = values.iter().flatten().copied().reduce(f64::min)?;
+    let max = values.iter().flatten().copied().reduce(f64::max)?;
+    Some(Sparkline {
+        label: label.to_string(),
+        note: format!("{} – {}", format(min), format(max)),
+        values,
+    })
+}
+
+fn generate_latency_distribution_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let units = &options.units;
+    let mut names = latency_metric_names(summary);
+    names.retain(|name| options.shows(name));
+
+    let mut document = Document::new();
+    if names.is_empty() {
+        return document;
+    }
+
+    document.heading(2, "Latency Distribution");
+
+    for name in names {
+        let sorted = &summary.samples[name];
+        let total = sorted.len();
+
+        document.heading(3, name.as_str());
+
+        let mut chart = BarChart::new(["Range", "Count", "%", "Distribution"]);
+        for bucket in log_histogram(sorted) {
+            chart.bar(
+                vec![
+                    format!(
+                        "{} – {}",
+                        units.duration(bucket.lower),
+                        units.duration(bucket.upper)
+                    ),
+                    bucket.count.to_string(),
+                    units.percent(bucket.count as f64 / total as f64),
+                ],
+                bucket.count,
+            );
+        }
+        document.push(chart);
+
+        let mut table = Table::new(["Percentile", "Value", "Total Count", "1/(1-P)"]);
+        for p in SPECTRUM_PERCENTILES {
+            let value = value_at_percentile(sorted, p / 100.0);
+            let count = sorted.partition_point(|v| *v <= value);
+            let inverse = if p >= 100.0 {
+                "∞".to_string()
+            } else {
+                units.number(100.0 / (100.0 - p))
+            };
+            table.row([
+                format!("p{}", p),
+                units.duration(value),
+                count.to_string(),
+                inverse,
+            ]);
+        }
+        document.push(table);
+    }
+
+    document.rule();
+    document
+}
+
+fn generate_checks_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let checks = match &summary.root_group {
+        Some(group) => collect_checks(group),
+        None => Vec::new(),
+    };
+
+    let mut document = Document::new();
+    if checks.is_empty() {
+        return document;
+    }
+
+    document.heading(2, "Checks");
+    let mut table = Table::new(["Check", "Passes", "Fails", "Success Rate"]);
+
+    for check in checks {
+        let total = check.passes + check.fails;
+        let rate = if total > 0 {
+            check.passes as f64 / total as f64
+        } else {
+            1.0
+        };
+        let (status, icon) = if check.fails == 0 {
+            (Status::Pass, "✓")
+        } else {
+            (Status::Fail, "✗")
+        };
+        table.row([
+            Text::status(status, format!("{} {}", icon, check.name)),
+            check.passes.to_string().into(),
+            check.fails.to_string().into(),
+            options.units.percent(rate).into(),
+        ]);
+    }
+    document.push(table);
+
+    document.rule();
+    document
+}
+
+pub(crate) fn collect_checks(group: &Group) -> Vec<&Check> {
+    let mut checks: Vec<&Check> = group.checks.iter().collect();
+    for subgroup in &group.groups {
+        checks.extend(collect_checks(subgroup));
+    }
+    checks
+}
+
+fn generate_route_section(
+    summary: &K6Summary,
+    route: &MetricRoute,
+    options: &ReportOptions,
+) -> Document {
+    let mut metrics: Vec<(&String, &Metric)> = summary
+        .metrics
+        .iter()
+        .filter(|(name, _)| {
+            options.shows(name)
+                && options
+                    .route_for(name)
+                    .is_some_and(|r| std::ptr::eq(r, route))
+        })
+        .collect();
+
+    let mut document = Document::new();
+    if metrics.is_empty() {
+        return document;
+    }
+    metrics.sort_by(|a, b| a.0.cmp(b.0));
+
+    let columns: Vec<String> = if route.columns.is_empty() {
+        let mut all: HashMap<String, f64> = HashMap::new();
+        for (_, metric) in &metrics {
+            all.extend(metric.values.iter().map(|(k, v)| (k.clone(), *v)));
+        }
+        sort_stats(&all, &options.trend_stats)
+            .into_iter()
+            .map(|(key, _)| key.clone())
+            .collect()
+    } else {
+        route.columns.clone()
+    };
+
+    document.heading(2, route.heading.as_str());
+    let mut table = Table::new(std::iter::once("Metric".to_string()).chain(columns.clone()));
+
+    for (name, metric) in metrics {
+        let cells = columns.iter().map(|key| match metric.values.get(key) {
+            Some(value) => options.units.value(*value, key, name, metric),
+            None => "-".to_string(),
+        });
+        table.row(std::iter::once(name.clone()).chain(cells));
+    }
+    document.push(table);
+
+    document.rule();
+    document
+}
+
+fn generate_all_metrics_section(summary: &K6Summary, options: &ReportOptions) -> Document {
+    let units = &options.units;
+    let mut document = Document::new();
+    document.heading(2, "All Metrics");
+
+    let mut counters: Vec<(&String, &Metric)> = Vec::new();
+    let mut rates: Vec<(&String, &Metric)> = Vec::new();
+    let mut gauges: Vec<(&String, &Metric)> = Vec::new();
+    let mut trends: Vec<(&String, &Metric)> = Vec::new();
+
+    for (name, metric) in &summary.metrics {
+        if name.starts_with("http_") || !options.shows_unrouted(name) {
+            continue;
+        }
+        match metric.metric_type {
+            MetricType::Counter => counters.push((name, metric)),
+            MetricType::Rate => rates.push((name, metric)),
+            MetricType::Gauge => gauges.push((name, metric)),
+            MetricType::Trend => trends.push((name, metric)),
+        }
+    }
+
+    if !counters.is_empty() {
+        document.heading(3, "Counters");
+        let mut table = Table::new(["Metric", "Count", "Rate"]);
+        counters.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, metric) in &counters {
+            let count = metric.values.get("count").copied().unwrap_or(0.0);
+            let rate = metric.values.get("rate").copied().unwrap_or(0.0);
+            table.row([
+                name.to_string(),
+                units.value(count, "count", name, metric),
+                units.value(rate, "rate", name, metric),
+            ]);
+        }
+        document.push(table);
+    }
+
+    if !rates.is_empty() {
+        document.heading(3, "Rates");
+        let mut table = Table::new(["Metric", "Rate", "Passes", "Fails"]);
+        rates.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, metric) in &rates {
+            let rate = metric.values.get("rate").copied().unwrap_or(0.0);
+            let passes = metric.values.get("passes").copied().unwrap_or(0.0);
+            let fails = metric.values.get("fails").copied().unwrap_or(0.0);
+            table.row([
+                name.to_string(),
+                units.percent(rate),
+                units.count(passes),
+                units.count(fails),
+            ]);
+        }
+        document.push(table);
+    }
+
+    if !gauges.is_empty() {
+        document.heading(3, "Gauges");
+        let mut table = Table::new(["Metric", "Value", "Min", "Max"]);
+        gauges.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, metric) in &gauges {
+            let value = metric.values.get("value").copied().unwrap_or(0.0);
+            let min = metric.values.get("min").copied().unwrap_or(0.0);
+            let max = metric.values.get("max").copied().unwrap_or(0.0);
+            table.row([
+                name.to_string(),
+                units.value(value, "value", name, metric),
+                units.value(min, "min", name, metric),
+                units.value(max, "max", name, metric),
+            ]);
+        }
+        document.push(table);
+    }
+
+    if !trends.is_empty() {
+        document.heading(3, "Trends");
+        trends.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, metric) in &trends {
+            document.paragraph(Text::strong(name.as_str()));
+            document.push(stat_list(name, metric, options));
+        }
+    }
+
+    document
+}
+
+// =============================================================================
+// Live Dashboard
+// =============================================================================
+
+/// The `k6r live` dashboard: where the run stands now, how the current rates
+/// moved over `history`, and the thresholds on the data so far
+pub fn build_live_document(
+    stats: &RollingStats,
+    history: &[LiveSnapshot],
+    window_ms: f64,
+    options: &ReportOptions,
+) -> Document {
+    let units = &options.units;
+    let now = history.last().copied().unwrap_or_else(|| stats.snapshot());
+    let optional = |value: Option<f64>, format: &dyn Fn(f64) -> String| {
+        value.map_or_else(|| "-".to_string(), format)
+    };
+
+    let mut document = Document::new();
+    document.heading(1, format!("{} (live)", options.title));
+
+    let mut current = KeyValueList::new();
+    current.row("Elapsed", format_offset(now.elapsed_ms));
+    current.row("VUs", optional(now.vus, &|vus| format!("{}", vus)));
+    current.row("Requests", units.count(now.requests));
+    current.row("Requests/s", optional(now.rps, &|rate| units.number(rate)));
+    current.row("P95", optional(now.p95, &|ms| units.duration(ms)));
+    current.row(
+        "Errors",
+        optional(now.error_rate, &|rate| units.percent(rate)),
+    );
+    document.push(current);
+    document.paragraph(Text::emphasis(format!(
+        "Requests/s, P95 and Errors over the last {}s",
+        window_ms / 1000.0
+    )));
+
+    let series = |values: fn(&LiveSnapshot) -> Option<f64>| -> Vec<Option<f64>> {
+        history.iter().map(values).collect()
+    };
+    let sparklines = [
+        sparkline("Requests/s", series(|s| s.rps), |rate| units.number(rate)),
+        sparkline("P95", series(|s| s.p95), |ms| units.duration(ms)),
+        sparkline("Errors", series(|s| s.error_rate), |rate| {
+            units.percent(rate)
+        }),
+    ];
+    let sparklines: Vec<Sparkline> = sparklines.into_iter().flatten().collect();
+    if !sparklines.is_empty() {
+        document.push(Block::Sparklines(sparklines));
+    }
+
+    let thresholds = stats.thresholds();
+    if !thresholds.is_empty() {
+        document.heading(2, "Thresholds");
+        let mut table = Table::new(["Metric", "Threshold", "Status"]);
+        for (metric_name, threshold_expr, result) in &thresholds {
+            table.row([
+                Text::plain(*metric_name),
+                Text::code(*threshold_expr),
+                threshold_status(result),
+            ]);
+        }
+        document.push(table);
+    }
+    document
+}
+
+// =============================================================================
+// Index Pages
+// =============================================================================
+
+/// Title of an index page unless one is configured
+pub const DEFAULT_INDEX_TITLE: &str = "K6 Load Test Runs";
+
+/// A run listed on an index page
+#[non_exhaustive]
+pub struct IndexEntry {
+    /// Shown for the run, e.g. its result file name
+    pub name: String,
+    /// Where the run's report is, relative to the index
+    pub href: String,
+    /// The run's headline numbers
+    pub overview: RunOverview,
+}
+
+impl IndexEntry {
+    /// An entry linking to `href`
+    pub fn new(name: impl Into<String>, href: impl Into<String>, overview: RunOverview) -> Self {
+        IndexEntry {
+            name: name.into(),
+            href: href.into(),
+            overview,
+        }
+    }
+}
+
+/// A page listing runs with their headline numbers, each linked to its report.
+/// `skipped` lists inputs that could not be reported with the reason.
+pub fn build_index_document(
+    title: &str,
+    entries: &[IndexEntry],
+    skipped: &[(String, String)],
+    units: &Units,
+) -> Document {
+    let optional = |value: Option<f64>, format: &dyn Fn(f64) -> String| {
+        value.map_or_else(|| "-".to_string(), format)
+    };
+
+    let mut document = Document::new();
+    document.heading(1, title);
+
+    let mut table = Table::new([
+        "Run",
+        "Duration",
+        "Requests/s",
+        "P95",
+        "Errors",
+        "Thresholds",
+    ]);
+    for entry in entries {
+        let run = &entry.overview;
+        let verdict = match run.verdict {
+            None => Text::plain("-"),
+            Some(Verdict::Passed) => Text::status(Status::Pass, "✓ PASS"),
+            Some(Verdict::Failed) => {
+                Text::status(Status::Fail, Text::plain("✗ ").then(Text::strong("FAIL")))
+            }
+            Some(Verdict::Unknown) => Text::status(Status::Unknown, "? N/A"),
+        };
+        table.row([
+            Text::link(entry.name.as_str(), entry.href.as_str()),
+            Text::plain(optional(run.duration_ms, &|ms| units.elapsed(ms))),
+            Text::plain(optional(run.rps, &|rate| units.number(rate))),
+            Text::plain(optional(run.p95, &|ms| units.duration(ms))),
+            Text::plain(optional(run.error_rate, &|rate| units.percent(rate))),
+            verdict,
+        ]);
+    }
+    if entries.is_empty() {
+        document.paragraph(Text::emphasis("No runs."));
+    } else {
+        document.push(table);
+    }
+
+    if !skipped.is_empty() {
+        document.heading(2, "Skipped");
+        let lines = skipped
+            .iter()
+            .map(|(name, reason)| Text::code(name.as_str()).then(format!(": {}", reason)));
+        document.push(Block::Quote(lines.collect()));
+    }
+    document
+}
+
+/// Percent-encodes a file name for use in a relative link
+pub fn url_path(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// =============================================================================
+// Report Templates
+// =============================================================================
+
+const DEFAULT_TEMPLATE_NAME: &str = "default.md.j2";
+/// Source of the built-in report template
+pub const DEFAULT_TEMPLATE: &str = include_str!("../templates/default.md.j2");
+
+/// Everything a report template can use. Raw values are in k6's units
+/// (milliseconds, bytes); `display` holds them formatted like the built-in
+/// report.
+#[derive(Debug, Serialize)]
+struct ReportData<'a> {
+    title: &'a str,
+    meta: Vec<MetaRow<'a>>,
+    /// Formatted test run duration, if known
+    duration: Option<String>,
+    duration_ms: Option<f64>,
+    window: Option<WindowData>,
+    /// `--tag` filters as written on the command line
+    filters: Vec<String>,
+    warnings: &'a [String],
+    /// All metrics by name, whether or not --include/--exclude list them
+    metrics: Vec<MetricData<'a>>,
+    /// Every threshold of every metric, by metric then expression
+    thresholds: Vec<ThresholdData<'a>>,
+    checks: Vec<CheckData<'a>>,
+    endpoints: &'a [EndpointStats],
+    timeline: &'a [TimelineBucket],
+    /// The selected built-in sections as Markdown, in report order
+    sections: Vec<SectionData>,
+    /// Built-in sections as Markdown by name: the selected ones and those the
+    /// template looks up
+    section: BTreeMap<&'static str, String>,
+}
+
+#[derive(Debug, Serialize)]
+struct MetaRow<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct WindowData {
+    start_ms: f64,
+    end_ms: f64,
+    run_ms: f64,
+    steady_vus: Option<f64>,
+    /// Offsets formatted as m:ss or h:mm:ss
+    start: String,
+    end: String,
+    run: String,
+}
+
+#[derive(Debug, Serialize)]
+struct MetricData<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    metric_type: MetricType,
+    contains: &'a str,
+    values: BTreeMap<&'a str, f64>,
+    display: BTreeMap<&'a str, String>,
+    thresholds: Vec<ThresholdData<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ThresholdData<'a> {
+    metric: &'a str,
+    expression: &'a str,
+    ok: bool,
+    /// The expression could not be evaluated, e.g. for lack of data
+    unknown: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckData<'a> {
+    name: &'a str,
+    passes: u64,
+    fails: u64,
+    rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct SectionData {
+    name: &'static str,
+    content: String,
+}
+
+impl<'a> ReportData<'a> {
+    /// `referenced` are the sections to build for the `section` map besides
+    /// the selected ones; each section is built once
+    fn new(summary: &'a K6Summary, options: &'a ReportOptions, referenced: &[Section]) -> Self {
+        let units = &options.units;
+
+        let mut metrics: Vec<MetricData> = summary
+            .metrics
+            .iter()
+            .map(|(name, metric)| {
+                let mut thresholds: Vec<ThresholdData> = metric
+                    .thresholds
+                    .iter()
+                    .map(|(expression, threshold)| ThresholdData {
+                        metric: name,
+                        expression,
+                        ok: threshold.ok,
+                        unknown: threshold.unknown,
+                    })
+                    .collect();
+                thresholds.sort_by(|a, b| a.expression.cmp(b.expression));
+                MetricData {
+                    name,
+                    metric_type: metric.metric_type,
+                    contains: &metric.contains,
+                    values: metric
+                        .values
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), *value))
+                        .collect(),
+                    display: metric
+                        .values
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), units.value(*value, key, name, metric)))
+                        .collect(),
+                    thresholds,
+                }
+            })
+            .collect();
+        metrics.sort_by(|a, b| a.name.cmp(b.name));
+
+        let checks = match &summary.root_group {
+            Some(group) => collect_checks(group),
+            None => Vec::new(),
+        };
+
+        let section: BTreeMap<&'static str, String> = Section::ALL
+            .iter()
+            .filter(|section| options.sections.contains(section) || referenced.contains(section))
+            .map(|section| {
+                let content = Markdown.render(&section.build(summary, options));
+                (section.name(), content)
+            })
+            .collect();
+
+        ReportData {
+            title: &options.title,
+            meta: options
+                .meta
+                .iter()
+                .map(|(key, value)| MetaRow { key, value })
+                .collect(),
+            duration: summary
+                .state
+                .as_ref()
+                .map(|state| units.elapsed(state.test_run_duration_ms)),
+            duration_ms: summary
+                .state
+                .as_ref()
+                .map(|state| state.test_run_duration_ms),
+            window: summary.window.as_ref().map(|window| WindowData {
+                start_ms: window.start_ms,
+                end_ms: window.end_ms,
+                run_ms: window.run_ms,
+                steady_vus: window.steady_vus,
+                start: format_offset(window.start_ms),
+                end: format_offset(window.end_ms),
+                run: format_offset(window.run_ms),
+            }),
+            filters: summary.filters.iter().map(|f| f.to_string()).collect(),
+            warnings: &summary.warnings,
+            thresholds: metrics
+                .iter()
+                .flat_map(|metric| metric.thresholds.iter().cloned())
+                .collect(),
+            metrics,
+            checks: checks
+                .into_iter()
+                .map(|check| {
+                    let total = check.passes + check.fails;
+                    CheckData {
+                        name: &check.name,
+                        passes: check.passes,
+                        fails: check.fails,
+                        rate: if total > 0 {
+                            check.passes as f64 / total as f64
+                        } else {
+                            1.0
+                        },
+                    }
+                })
+                .collect(),
+            endpoints: &summary.endpoints,
+            timeline: &summary.timeline,
+            sections: options
+                .sections
+                .iter()
+                .map(|selected| SectionData {
+                    name: selected.name(),
+                    content: section[selected.name()].clone(),
+                })
+                .collect(),
+            section,
+        }
+    }
+}
+
+/// The built-in sections a template looks up in `section`; all of them when
+/// it does more than name them, such as `section[name]` or a loop over it
+fn referenced_sections(template: &minijinja::Template) -> Vec<Section> {
+    let variables = template.undeclared_variables(true);
+    if variables.contains("section") {
+        return Section::ALL.to_vec();
+    }
+    Section::ALL
+        .into_iter()
+        .filter(|section| variables.contains(&format!("section.{}", section.name())))
+        .collect()
+}
+
+/// Renders a report template against the summary. Block tags swallow the
+/// newline after them so templates can keep one tag per line.
+pub fn render_template(
+    name: &str,
+    source: &str,
+    summary: &K6Summary,
+    options: &ReportOptions,
+) -> Result<String, Error> {
+    let template_error = |e: minijinja::Error| Error::Template {
+        name: name.to_string(),
+        message: format!("{:#}", e),
+    };
+
+    let mut env = minijinja::Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_keep_trailing_newline(true);
+    env.add_template(name, source).map_err(template_error)?;
+    let template = env.get_template(name).map_err(template_error)?;
+    let referenced = referenced_sections(&template);
+    template
+        .render(ReportData::new(summary, options, &referenced))
+        .map_err(template_error)
+}
+
+/// The data a report template sees, as JSON
+pub fn report_json(summary: &K6Summary, options: &ReportOptions) -> String {
+    serde_json::to_string_pretty(&ReportData::new(summary, options, &Section::ALL))
+        .unwrap_or_default()
+}
+
+// =============================================================================
+// Output Formats
+// =============================================================================
+
+/// Turns a [`Document`] into one output format
+pub trait Renderer {
+    /// The whole document in this format
+    fn render(&self, document: &Document) -> String;
+}
+
+/// Report formats k6r can write
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// GitHub-flavored Markdown
+    #[default]
+    Markdown,
+    /// A standalone HTML page
+    Html,
+    /// Plain text with aligned columns
+    Text,
+    /// Text for the terminal, with colors and sparklines when stdout is one
+    Term,
+}
+
+impl OutputFormat {
+    /// File extension of reports in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "md",
+            OutputFormat::Html => "html",
+            OutputFormat::Text | OutputFormat::Term => "txt",
+        }
+    }
+
+    /// The renderer writing this format
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        match self {
+            OutputFormat::Markdown => Box::new(Markdown),
+            OutputFormat::Html => Box::new(Html),
+            OutputFormat::Text => Box::new(PlainText),
+            OutputFormat::Term => Box::new(Terminal::for_stdout()),
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze::format_hgrm;
+    use crate::parse::{parse_jsonl, parse_summary_export, ParseOptions};
+    use crate::State;
+
+    #[test]
+    fn test_format_duration() {
+        let units = Units::default();
+        assert_eq!(units.duration(0.5), "500.00µs");
+        assert_eq!(units.duration(1.0), "1.00ms");
+        assert_eq!(units.duration(150.5), "150.50ms");
+        assert_eq!(units.duration(1500.0), "1.50s");
+        assert_eq!(units.duration(90000.0), "1.50m");
+    }
+
+    #[test]
+    fn test_format_duration_fixed_unit() {
+        let units = Units {
+            time_unit: TimeUnit::S,
+            precision: 3,
+            ..Units::default()
+        };
+        assert_eq!(units.duration(150.5), "0.150s");
+        assert_eq!(units.elapsed(90000.0), "1.500m");
+    }
+
+    #[test]
+    fn test_format_count() {
+        let units = Units::default();
+        assert_eq!(units.count(50.0), "50");
+        assert_eq!(units.count(1500.0), "1.50K");
+        assert_eq!(units.count(2500000.0), "2.50M");
+    }
+
+    #[test]
+    fn test_format_percent() {
+        let units = Units::default();
+        assert_eq!(units.percent(0.0), "0.00%");
+        assert_eq!(units.percent(0.5), "50.00%");
+        assert_eq!(units.percent(1.0), "100.00%");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        let units = Units::default();
+        assert_eq!(units.bytes(512.0), "512B");
+        assert_eq!(units.bytes(1536.0), "1.50KiB");
+        assert_eq!(units.bytes(1_572_864.0), "1.50MiB");
+    }
+
+    #[test]
+    fn test_format_value_data_metric() {
+        let units = Units::default();
+        let metric = Metric {
+            metric_type: MetricType::Counter,
+            contains: "data".to_string(),
+            values: HashMap::new(),
+            thresholds: HashMap::new(),
+        };
+        assert_eq!(
+            units.value(1_572_864.0, "count", "data_received", &metric),
+            "1.50MiB"
+        );
+        assert_eq!(
+            units.value(2_097_152.0, "rate", "data_received", &metric),
+            "2.00MiB/s"
+        );
+    }
+
+    #[test]
+    fn test_format_value_declared_unit() {
+        let units = Units {
+            metric_units: [("payload_size".to_string(), Unit::Bytes)]
+                .into_iter()
+                .collect(),
+            ..Units::default()
+        };
+        let metric = Metric {
+            metric_type: MetricType::Trend,
+            contains: "default".to_string(),
+            values: HashMap::new(),
+            thresholds: HashMap::new(),
+        };
+        assert_eq!(
+            units.value(2048.0, "avg", "payload_size", &metric),
+            "2.00KiB"
+        );
+        assert_eq!(
+            units.value(2048.0, "count", "payload_size", &metric),
+            "2.05K"
+        );
+        assert_eq!(units.value(2048.0, "avg", "other", &metric), "2048.00");
+    }
+
+    #[test]
+    fn test_timeline() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let summary = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+
+        // A ~9.5s run is split into one-second intervals
+        assert_eq!(summary.timeline.len(), 10);
+        assert_eq!(summary.timeline[1].start_ms, 1000.0);
+        let requests: f64 = summary.timeline.iter().map(|b| b.requests).sum();
+        assert_eq!(requests, 44.0);
+        let peak = summary
+            .timeline
+            .iter()
+            .filter_map(|b| b.vus)
+            .fold(0.0, f64::max);
+        assert_eq!(peak, 3.0);
+
+        let report = generate_report(&summary, &ReportOptions::default());
+        assert!(report.contains("## Timeline"));
+        assert!(report.contains("| 0:01 | 2 | 5.00 |"));
+    }
+
+    #[test]
+    fn test_metric_route_from_str() {
+        let route: MetricRoute = "Kafka = kafka_writer_*, kafka_reader_*:count,rate"
+            .parse()
+            .unwrap();
+        assert_eq!(route.heading, "Kafka");
+        assert_eq!(route.patterns, vec!["kafka_writer_*", "kafka_reader_*"]);
+        assert_eq!(route.columns, vec!["count", "rate"]);
+
+        let route: MetricRoute = "SQL=sql_*".parse().unwrap();
+        assert!(route.columns.is_empty());
+        assert!("sql_*".parse::<MetricRoute>().is_err());
+        assert!("SQL=".parse::<MetricRoute>().is_err());
+    }
+
+    #[test]
+    fn test_metric_selection_and_routes() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let summary = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+        let options = ReportOptions {
+            exclude: vec!["data_*".to_string()],
+            routes: vec![
+                "Work=items_*,iteration*:count,rate,avg".parse().unwrap(),
+                "Waiting=http_req_waiting".parse().unwrap(),
+            ],
+            ..ReportOptions::default()
+        };
+        let report = generate_report(&summary, &options);
+
+        assert!(report.contains("## Work\n\n| Metric | count | rate | avg |\n"));
+        assert!(report.contains("| items_processed | 132 | "));
+        assert!(report.contains("| iteration_duration | - | - | "));
+        assert!(report.contains("## Waiting\n\n| Metric | avg | min | med |"));
+        // Routed and excluded metrics leave the built-in sections
+        assert!(!report.contains("### http_req_waiting (trend)"));
+        assert!(!report.contains("### Counters"));
+        assert!(!report.contains("| data_sent |"));
+        assert!(report.contains("### http_req_duration"));
+
+        let options = ReportOptions {
+            include: vec!["vus*".to_string()],
+            ..ReportOptions::default()
+        };
+        let report = generate_report(&summary, &options);
+        assert!(report.contains("| vus_max |"));
+        assert!(!report.contains("## HTTP Metrics"));
+        assert!(!report.contains("### Counters"));
+    }
+
+    #[test]
+    fn test_endpoints_section() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let summary = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+        let report = generate_report(&summary, &ReportOptions::default());
+
+        assert!(report.contains("## HTTP Endpoints"));
+        assert!(report.contains("| GET https://test.k6.io/ | 23 |"));
+        assert!(report.contains("| GET https://test.k6.io/contacts.php | 21 |"));
+        assert!(report.contains("500: 2"));
+    }
+
+    #[test]
+    fn test_generate_report_not_empty() {
+        let mut metrics = HashMap::new();
+        metrics.insert(
+            "http_reqs".to_string(),
+            Metric {
+                metric_type: MetricType::Counter,
+                contains: "default".to_string(),
+                values: [("count".to_string(), 100.0), ("rate".to_string(), 10.0)]
+                    .into_iter()
+                    .collect(),
+                thresholds: HashMap::new(),
+            },
+        );
+
+        let summary = K6Summary {
+            metrics,
+            root_group: None,
+            state: Some(State {
+                test_run_duration_ms: 10000.0,
+            }),
+            ..Default::default()
+        };
+
+        let report = generate_report(&summary, &ReportOptions::default());
+
+        assert!(report.contains("# K6 Load Test Report"));
+        assert!(report.contains("10.00s"));
+        assert!(report.contains("100"));
+        assert!(!report.contains("## Latency Distribution"));
+    }
+
+    #[test]
+    fn test_spectrum_matches_hgrm() {
+        let sorted: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+        let hgrm = format_hgrm(&sorted);
+        let summary = K6Summary {
+            samples: HashMap::from([("http_req_duration".to_string(), sorted)]),
+            ..Default::default()
+        };
+        let options = ReportOptions {
+            units: Units {
+                time_unit: TimeUnit::Ms,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let report = generate_report(&summary, &options);
+
+        // Nearest rank in both; interpolation would put the median at 5.5
+        assert!(report.contains("| p50 | 5.00ms | 5 |"), "{}", report);
+        assert!(hgrm.contains("       5.000 0.500000000000 "), "{}", hgrm);
+    }
+
+    #[test]
+    fn test_sort_stats_orders_custom_stats() {
+        let values: HashMap<String, f64> =
+            ["count", "p(99.9)", "max", "p(90)", "avg", "med", "min"]
+                .iter()
+                .map(|k| (k.to_string(), 1.0))
+                .collect();
+
+        let keys: Vec<&str> = sort_stats(&values, &default_trend_stats())
+            .into_iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["avg", "min", "med", "max", "p(90)", "p(99.9)", "count"]
+        );
+
+        let configured = vec![TrendStat::Count, TrendStat::Percentile(99.9)];
+        let keys: Vec<&str> = sort_stats(&values, &configured)
+            .into_iter()
+            .map(|(k, _)| k.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["count", "p(99.9)", "avg", "min", "med", "max", "p(90)"]
+        );
+    }
+
+    #[test]
+    fn test_effective_scenarios_shortcuts() {
+        let options = TestOptions {
+            vus: Some(10),
+            duration: Some("30s".to_string()),
+            ..Default::default()
+        };
+        let scenarios = effective_scenarios(&options);
+        assert_eq!(scenarios.len(), 1);
+        assert_eq!(scenarios[0].0, "default");
+        assert_eq!(scenarios[0].1.executor, "constant-vus");
+        assert_eq!(describe_load(&scenarios[0].1), "10 VUs");
+
+        assert!(effective_scenarios(&TestOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_report_sections_follow_options() {
+        let json = include_str!("../tests/fixtures/k6-run-summary.json");
+        let summary: K6Summary = serde_json::from_str(json).unwrap();
+        let report = generate_report(
+            &summary,
+            &ReportOptions {
+                title: "Checkout".to_string(),
+                meta: vec![("env".to_string(), "staging".to_string())],
+                sections: vec![Section::Metrics, Section::Summary],
+                ..Default::default()
+            },
+        );
+
+        assert!(report.starts_with("# Checkout\n\n| Key | Value |"));
+        assert!(report.contains("| env | staging |"));
+        let metrics = report.find("## All Metrics").unwrap();
+        let summary_at = report.find("## Summary").unwrap();
+        assert!(metrics < summary_at);
+        assert!(!report.contains("## HTTP Metrics"));
+    }
+
+    #[test]
+    fn test_report_meta_escapes_pipes() {
+        let report = generate_report(
+            &K6Summary::default(),
+            &ReportOptions {
+                meta: vec![("branch".to_string(), "a|b".to_string())],
+                sections: Vec::new(),
+                ..Default::default()
+            },
+        );
+        assert!(report.contains("| branch | a\\|b |"));
+    }
+
+    #[test]
+    fn test_render_custom_template() {
+        let json = include_str!("../tests/fixtures/k6-summary-export.json");
+        let mut summary = parse_summary_export(json).unwrap();
+        let thresholds =
+            BTreeMap::from([("http_req_failed".to_string(), vec!["rate<0.01".to_string()])]);
+        crate::analyze::apply_thresholds(&mut summary, &thresholds);
+        let options = ReportOptions {
+            title: "Nightly".to_string(),
+            ..Default::default()
+        };
+        let template = r#"{{ title }}
+{% for t in thresholds if not t.ok %}
+FAIL {{ t.metric }} {{ t.expression }}
+{% endfor %}
+{% for m in metrics if m.name == "http_req_duration" %}
+p95={{ m.display["p(95)"] }} raw={{ m.values.max }}
+{% endfor %}
+{% for c in checks %}
+{{ c.name }}: {{ c.passes }}/{{ c.passes + c.fails }}
+{% endfor %}
+{{ section.thresholds | length > 0 }}
+"#;
+        let report = render_template("custom.md", template, &summary, &options).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Nightly");
+        assert_eq!(lines[1], "FAIL http_req_failed rate<0.01");
+        assert!(lines[2].starts_with("p95=") && lines[2].ends_with("ms raw=1491.68"));
+        assert_eq!(lines[3], "status is 200: 42/44");
+        assert_eq!(lines[4], "True");
+    }
+
+    #[test]
+    fn test_template_builds_referenced_sections() {
+        let mut env = minijinja::Environment::new();
+        env.add_template("named", "{{ section.checks }}{{ section.timeline }}")
+            .unwrap();
+        env.add_template("indexed", "{{ section[name] }}").unwrap();
+
+        let named = referenced_sections(&env.get_template("named").unwrap());
+        assert_eq!(named, vec![Section::Timeline, Section::Checks]);
+        let indexed = referenced_sections(&env.get_template("indexed").unwrap());
+        assert_eq!(indexed, Section::ALL.to_vec());
+
+        let summary = K6Summary::default();
+        let options = ReportOptions {
+            sections: vec![Section::Summary],
+            ..Default::default()
+        };
+        let data = ReportData::new(&summary, &options, &named);
+        let built: Vec<&str> = data.section.keys().copied().collect();
+        assert_eq!(built, vec!["checks", "summary", "timeline"]);
+        assert_eq!(data.sections[0].content, data.section["summary"]);
+    }
+
+    #[test]
+    fn test_render_template_errors() {
+        let summary = K6Summary::default();
+        let options = ReportOptions::default();
+
+        let err = render_template("bad.md", "{% for m in metrics %}", &summary, &options);
+        let Err(Error::Template { name, message }) = err else {
+            panic!("expected a template error");
+        };
+        assert_eq!(name, "bad.md");
+        assert!(message.contains("bad.md:1"), "{}", message);
+
+        let err = render_template("bad.md", "{{ metrics | nope }}", &summary, &options);
+        assert!(matches!(err, Err(Error::Template { .. })));
+    }
+
+    #[test]
+    fn test_markdown_document_matches_template() {
+        let jsonl = include_str!("../tests/fixtures/k6-run.jsonl");
+        let mut summary = parse_jsonl(jsonl.as_bytes(), &ParseOptions::default()).unwrap();
+        summary.filters = vec!["method=GET".parse().unwrap(), "status!=5*".parse().unwrap()];
+        summary.window = Some(crate::TimeWindow {
+            start_ms: 1000.0,
+            end_ms: 8000.0,
+            run_ms: 9500.0,
+            steady_vus: Some(3.0),
+        });
+        summary.warnings = vec!["first | problem".to_string(), "second".to_string()];
+        let options = ReportOptions {
+            meta: vec![("branch".to_string(), "a|b".to_string())],
+            routes: vec!["Custom=vus*,iter*".parse().unwrap()],
+            ..Default::default()
+        };
+
+        let document = build_document(&summary, &options);
+        assert_eq!(
+            Markdown.render(&document),
+            generate_report(&summary, &options)
+        );
+
+        let json = include_str!("../tests/fixtures/k6-summary-export.json");
+        let summary = parse_summary_export(json).unwrap();
+        let options = ReportOptions::default();
+        assert_eq!(
+            Markdown.render(&build_document(&summary, &options)),
+            generate_report(&summary, &options)
+        );
+    }
+
+    #[test]
+    fn test_live_document() {
+        let mut stats = RollingStats::new(
+            10_000.0,
+            BTreeMap::from([("http_req_failed".to_string(), vec!["rate<0.5".to_string()])]),
+        );
+        let mut history = Vec::new();
+        for second in 0..5 {
+            let time = Some(second as f64 * 1000.0);
+            stats.add_point("http_reqs", MetricType::Counter, time, 10.0);
+            stats.add_point("http_req_duration", MetricType::Trend, time, 120.0);
+            stats.add_point("http_req_failed", MetricType::Rate, time, 0.0);
+            history.push(stats.snapshot());
+        }
+
+        let text = PlainText.render(&build_live_document(
+            &stats,
+            &history,
+            10_000.0,
+            &ReportOptions::default(),
+        ));
+        assert!(text.starts_with("K6 Load Test Report (live)\n"), "{}", text);
+        assert!(text.contains("Requests:   50\n"), "{}", text);
+        assert!(text.contains("Requests/s: 12.50\n"), "{}", text);
+        assert!(text.contains("P95:        120.00ms\n"), "{}", text);
+        assert!(text.contains("over the last 10s"), "{}", text);
+        assert!(text.contains("Requests/s  "), "{}", text);
+        assert!(
+            text.contains("http_req_failed  rate<0.5   ✓ PASS"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn test_index_document() {
+        assert_eq!(url_path("run 1+2.md"), "run%201%2B2.md");
+
+        let entries = vec![IndexEntry {
+            name: "run 1.json".to_string(),
+            href: url_path("run 1.md"),
+            overview: RunOverview {
+                duration_ms: Some(9_500.0),
+                rps: Some(4.5),
+                p95: Some(314.0),
+                error_rate: Some(0.05),
+                verdict: Some(Verdict::Passed),
+            },
+        }];
+        let skipped = vec![("bad.json".to_string(), "Unrecognized".to_string())];
+        let document = build_index_document("Runs", &entries, &skipped, &Units::default());
+
+        let markdown = Markdown.render(&document);
+        assert!(markdown.starts_with("# Runs\n"), "{}", markdown);
+        assert!(
+            markdown.contains("| [run 1.json](run%201.md) | 9.50s | 4.50 | 314.00ms | 5.00% |"),
+            "{}",
+            markdown
+        );
+        assert!(
+            markdown.contains("> `bad.json`: Unrecognized"),
+            "{}",
+            markdown
+        );
+        let html = Html.render(&document);
+        assert!(
+            html.contains("<a href=\"run%201.md\">run 1.json</a>"),
+            "{}",
+            html
+        );
+
+        let empty = PlainText.render(&build_index_document("Runs", &[], &[], &Units::default()));
+        assert!(empty.contains("No runs."), "{}", empty);
+    }
+
+    #[test]
+    fn test_markdown_table_separators() {
+        let mut document = Document::new();
+        let mut table = Table::new(["Check", "Success Rate"]);
+        table.row([Text::plain("a|b"), Text::code("x")]);
+        document.push(table);
+        assert_eq!(
+            Markdown.render(&document),
+            "| Check | Success Rate |\n|-------|--------------|\n| a\\|b | `x` |\n\n"
+        );
+    }
+
+    #[test]
+    fn test_output_format_renderers() {
+        let json = include_str!("../tests/fixtures/k6-run-summary.json");
+        let summary: K6Summary = serde_json::from_str(json).unwrap();
+        let document = build_document(&summary, &ReportOptions::default());
+
+        let html = OutputFormat::Html.renderer().render(&document);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>K6 Load Test Report</title>"));
+        assert!(html.contains("<h2>Summary</h2>"));
+
+        let text = OutputFormat::Text.renderer().render(&document);
+        assert!(text.starts_with("K6 Load Test Report\n===================\n"));
+        assert!(!text.contains('|') && !text.contains("**"));
+    }
+}
@@ -0,0 +1,336 @@
+//! A format-independent report. Section generators build a [`Document`] and
+//! a [`Renderer`](super::Renderer) turns it into Markdown, HTML or text.
+
+/// Width in characters of the longest bar in a [`BarChart`]
+pub const BAR_WIDTH: usize = 30;
+
+/// A report as a sequence of blocks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    /// The blocks in reading order
+    pub blocks: Vec<Block>,
+}
+
+impl Document {
+    /// An empty document
+    pub fn new() -> Self {
+        Document::default()
+    }
+
+    /// Whether the document has no blocks
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Adds a block at the end
+    pub fn push(&mut self, block: impl Into<Block>) {
+        self.blocks.push(block.into());
+    }
+
+    /// Adds a heading
+    pub fn heading(&mut self, level: u8, text: impl Into<String>) {
+        self.push(Block::Heading {
+            level,
+            text: text.into(),
+        });
+    }
+
+    /// Adds a paragraph
+    pub fn paragraph(&mut self, text: impl Into<Text>) {
+        self.push(Block::Paragraph(text.into()));
+    }
+
+    /// Adds a horizontal rule
+    pub fn rule(&mut self) {
+        self.push(Block::Rule);
+    }
+
+    /// Adds every block of another document
+    pub fn append(&mut self, other: Document) {
+        self.blocks.extend(other.blocks);
+    }
+}
+
+/// One block-level element of a [`Document`]
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Block {
+    /// Level 1 is the report title, 2 a section and 3 a subsection
+    Heading {
+        /// 1 to 3
+        level: u8,
+        /// The heading's text
+        text: String,
+    },
+    /// A paragraph of inline text
+    Paragraph(Text),
+    /// A table with a header row
+    Table(Table),
+    /// Labelled values
+    KeyValue(KeyValueList),
+    /// Rows with proportional bars
+    BarChart(BarChart),
+    /// Preformatted text, e.g. JSON
+    Code {
+        /// Language hint, e.g. `json`
+        language: String,
+        /// The text, shown as it is
+        text: String,
+    },
+    /// Highlighted notes such as input warnings
+    Quote(Vec<Text>),
+    /// Separates sections
+    Rule,
+    /// Series drawn as one line of bar characters each. They summarize data
+    /// shown elsewhere, so only the text renderers draw them.
+    Sparklines(Vec<Sparkline>),
+}
+
+impl From<Table> for Block {
+    fn from(table: Table) -> Self {
+        Block::Table(table)
+    }
+}
+
+impl From<KeyValueList> for Block {
+    fn from(list: KeyValueList) -> Self {
+        Block::KeyValue(list)
+    }
+}
+
+impl From<BarChart> for Block {
+    fn from(chart: BarChart) -> Self {
+        Block::BarChart(chart)
+    }
+}
+
+/// Rows of cells under column headers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// Column headers
+    pub headers: Vec<String>,
+    /// Cells of each row, one per header
+    pub rows: Vec<Vec<Text>>,
+}
+
+impl Table {
+    /// A table without rows
+    pub fn new<S: Into<String>>(headers: impl IntoIterator<Item = S>) -> Self {
+        Table {
+            headers: headers.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row
+    pub fn row<T: Into<Text>>(&mut self, cells: impl IntoIterator<Item = T>) {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+}
+
+/// Labelled values, shown as a two-column table when `headers` is set and as
+/// `label: value` lines otherwise
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyValueList {
+    /// Key and value column headers
+    pub headers: Option<[String; 2]>,
+    /// Labels and their values
+    pub rows: Vec<(String, Text)>,
+}
+
+impl KeyValueList {
+    /// A list without headers, shown as `label: value` lines
+    pub fn new() -> Self {
+        KeyValueList::default()
+    }
+
+    /// A list shown as a table with these column headers
+    pub fn with_headers(key: impl Into<String>, value: impl Into<String>) -> Self {
+        KeyValueList {
+            headers: Some([key.into(), value.into()]),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a labelled value
+    pub fn row(&mut self, key: impl Into<String>, value: impl Into<Text>) {
+        self.rows.push((key.into(), value.into()));
+    }
+}
+
+/// A table whose last column is a horizontal bar proportional to each row's value
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarChart {
+    /// Headers of the text columns followed by the bar column's
+    pub headers: Vec<String>,
+    /// The rows, in display order
+    pub bars: Vec<Bar>,
+}
+
+/// One row of a [`BarChart`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    /// Text columns
+    pub cells: Vec<String>,
+    /// What the bar's length is proportional to
+    pub value: usize,
+}
+
+impl BarChart {
+    /// A chart without bars
+    pub fn new<S: Into<String>>(headers: impl IntoIterator<Item = S>) -> Self {
+        BarChart {
+            headers: headers.into_iter().map(Into::into).collect(),
+            bars: Vec::new(),
+        }
+    }
+
+    /// Adds a row
+    pub fn bar(&mut self, cells: Vec<String>, value: usize) {
+        self.bars.push(Bar { cells, value });
+    }
+
+    /// Length of a bar in characters, with the largest one [`BAR_WIDTH`] long
+    pub fn width(&self, value: usize) -> usize {
+        let peak = self.bars.iter().map(|b| b.value).max().unwrap_or(0).max(1);
+        (value * BAR_WIDTH).div_ceil(peak)
+    }
+}
+
+/// A labelled series for [`Block::Sparklines`]; gaps are `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sparkline {
+    /// Shown before the line
+    pub label: String,
+    /// The series, one character each
+    pub values: Vec<Option<f64>>,
+    /// Shown after the line, e.g. the range of the values
+    pub note: String,
+}
+
+impl Sparkline {
+    const TICKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    /// The series as bar characters scaled from zero to its peak
+    pub fn line(&self) -> String {
+        let peak = self
+            .values
+            .iter()
+            .flatten()
+            .fold(0.0, |a: f64, &b| a.max(b));
+        self.values
+            .iter()
+            .map(|value| match value {
+                None => ' ',
+                Some(_) if peak <= 0.0 => Self::TICKS[0],
+                Some(v) => {
+                    let level = (v / peak * (Self::TICKS.len() - 1) as f64).round();
+                    Self::TICKS[(level.max(0.0) as usize).min(Self::TICKS.len() - 1)]
+                }
+            })
+            .collect()
+    }
+}
+
+/// Outcome shown by a [`Span::Status`], e.g. colored by the terminal renderer
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Status {
+    /// Passed
+    Pass,
+    /// Failed
+    Fail,
+    /// Could not be decided, e.g. a threshold without data
+    Unknown,
+}
+
+/// Inline text with optional emphasis
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Text(pub Vec<Span>);
+
+/// A piece of [`Text`] with one style
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Span {
+    /// Unstyled text
+    Plain(String),
+    /// Bold text
+    Strong(String),
+    /// Italic text
+    Emphasis(String),
+    /// Inline code
+    Code(String),
+    /// Text marking a pass or failure
+    Status(Status, Text),
+    /// Text linking to `href`, a URL or a path relative to the document
+    Link {
+        /// The link's text
+        text: Text,
+        /// Where it links to
+        href: String,
+    },
+}
+
+impl Text {
+    /// Unstyled text
+    pub fn plain(text: impl Into<String>) -> Self {
+        Text(vec![Span::Plain(text.into())])
+    }
+
+    /// Bold text
+    pub fn strong(text: impl Into<String>) -> Self {
+        Text(vec![Span::Strong(text.into())])
+    }
+
+    /// Italic text
+    pub fn emphasis(text: impl Into<String>) -> Self {
+        Text(vec![Span::Emphasis(text.into())])
+    }
+
+    /// Inline code
+    pub fn code(text: impl Into<String>) -> Self {
+        Text(vec![Span::Code(text.into())])
+    }
+
+    /// Text marking a pass or failure
+    pub fn status(status: Status, text: impl Into<Text>) -> Self {
+        Text(vec![Span::Status(status, text.into())])
+    }
+
+    /// Text linking to `href`
+    pub fn link(text: impl Into<Text>, href: impl Into<String>) -> Self {
+        Text(vec![Span::Link {
+            text: text.into(),
+            href: href.into(),
+        }])
+    }
+
+    /// Appends another piece of text
+    pub fn then(mut self, text: impl Into<Text>) -> Self {
+        self.0.extend(text.into().0);
+        self
+    }
+
+    /// The text without any emphasis
+    pub fn to_plain(&self) -> String {
+        self.0
+            .iter()
+            .map(|span| match span {
+                Span::Plain(s) | Span::Strong(s) | Span::Emphasis(s) | Span::Code(s) => s.clone(),
+                Span::Status(_, text) | Span::Link { text, .. } => text.to_plain(),
+            })
+            .collect()
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Text::plain(text)
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Text::plain(text)
+    }
+}
@@ -0,0 +1,196 @@
+//! A standalone HTML page with inline styles.
+
+use super::document::{Block, Document, Span, Status, Text};
+use super::Renderer;
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+table{border-collapse:collapse;margin:1em 0}\
+th,td{border:1px solid #ddd;padding:4px 10px;text-align:left}\
+th{background:#f5f5f5}\
+dt{font-weight:bold;float:left;clear:left;margin-right:.5em}\
+dd{margin:0 0 .25em}\
+blockquote{border-left:4px solid #e0a800;background:#fff8e1;margin:1em 0;padding:.5em 1em}\
+pre{background:#f5f5f5;padding:1em;overflow:auto}\
+.bar{display:inline-block;height:.8em;background:#4a90d9}\
+.pass{color:#1a7f37}\
+.fail{color:#cf222e}\
+.unknown{color:#9a6700}";
+
+/// A standalone HTML page with inline styles
+pub struct Html;
+
+impl Renderer for Html {
+    fn render(&self, document: &Document) -> String {
+        let title = document
+            .blocks
+            .iter()
+            .find_map(|block| match block {
+                Block::Heading { level: 1, text } => Some(text.as_str()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let mut output = String::with_capacity(16384);
+        output.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        output.push_str(&format!("<title>{}</title>\n", escape(title)));
+        output.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+        for block in &document.blocks {
+            render_block(&mut output, block);
+        }
+        output.push_str("</body>\n</html>\n");
+        output
+    }
+}
+
+fn render_block(output: &mut String, block: &Block) {
+    match block {
+        Block::Heading { level, text } => {
+            output.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(text)));
+        }
+        Block::Paragraph(text) => output.push_str(&format!("<p>{}</p>\n", inline(text))),
+        Block::Table(table) => {
+            let rows = table
+                .rows
+                .iter()
+                .map(|row| row.iter().map(inline).collect());
+            push_table(output, &table.headers, rows);
+        }
+        Block::KeyValue(list) => match &list.headers {
+            Some(headers) => {
+                let rows = list
+                    .rows
+                    .iter()
+                    .map(|(key, value)| vec![escape(key), inline(value)]);
+                push_table(output, headers, rows);
+            }
+            None => {
+                output.push_str("<dl>\n");
+                for (key, value) in &list.rows {
+                    output.push_str(&format!(
+                        "<dt>{}:</dt><dd>{}</dd>\n",
+                        escape(key),
+                        inline(value)
+                    ));
+                }
+                output.push_str("</dl>\n");
+            }
+        },
+        Block::BarChart(chart) => {
+            let rows = chart.bars.iter().map(|bar| {
+                let mut cells: Vec<String> = bar.cells.iter().map(|c| escape(c)).collect();
+                cells.push(format!(
+                    "<span class=\"bar\" style=\"width:{}ch\"></span>",
+                    chart.width(bar.value)
+                ));
+                cells
+            });
+            push_table(output, &chart.headers, rows);
+        }
+        Block::Code { language, text } => {
+            output.push_str(&format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>\n",
+                escape(language),
+                escape(text)
+            ));
+        }
+        Block::Quote(lines) => {
+            output.push_str("<blockquote>\n");
+            for line in lines {
+                output.push_str(&format!("<p>{}</p>\n", inline(line)));
+            }
+            output.push_str("</blockquote>\n");
+        }
+        Block::Rule => output.push_str("<hr>\n"),
+        Block::Sparklines(_) => {}
+    }
+}
+
+fn push_table(output: &mut String, headers: &[String], rows: impl Iterator<Item = Vec<String>>) {
+    output.push_str("<table>\n<thead><tr>");
+    for header in headers {
+        output.push_str(&format!("<th>{}</th>", escape(header)));
+    }
+    output.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        output.push_str("<tr>");
+        for cell in row {
+            output.push_str(&format!("<td>{}</td>", cell));
+        }
+        output.push_str("</tr>\n");
+    }
+    output.push_str("</tbody>\n</table>\n");
+}
+
+fn inline(text: &Text) -> String {
+    text.0
+        .iter()
+        .map(|span| match span {
+            Span::Plain(s) => escape(s),
+            Span::Strong(s) => format!("<strong>{}</strong>", escape(s)),
+            Span::Emphasis(s) => format!("<em>{}</em>", escape(s)),
+            Span::Code(s) => format!("<code>{}</code>", escape(s)),
+            Span::Status(status, text) => {
+                let class = match status {
+                    Status::Pass => "pass",
+                    Status::Fail => "fail",
+                    Status::Unknown => "unknown",
+                };
+                format!("<span class=\"{}\">{}</span>", class, inline(text))
+            }
+            Span::Link { text, href } => {
+                format!("<a href=\"{}\">{}</a>", escape(href), inline(text))
+            }
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::document::{BarChart, KeyValueList};
+
+    #[test]
+    fn test_html_escapes_text() {
+        let mut document = Document::new();
+        document.heading(1, "<Report>");
+        let mut list = KeyValueList::new();
+        list.row("Tag Filters", Text::code("name=\"a&b\""));
+        document.push(list);
+
+        let html = Html.render(&document);
+        assert!(html.contains("<title>&lt;Report&gt;</title>"));
+        assert!(html.contains("<h1>&lt;Report&gt;</h1>"));
+        assert!(
+            html.contains("<dt>Tag Filters:</dt><dd><code>name=&quot;a&amp;b&quot;</code></dd>")
+        );
+    }
+
+    #[test]
+    fn test_html_bar_chart() {
+        let mut chart = BarChart::new(["Range", "Distribution"]);
+        chart.bar(vec!["0 – 1".to_string()], 4);
+        chart.bar(vec!["1 – 2".to_string()], 1);
+        let mut document = Document::new();
+        document.push(chart);
+
+        let html = Html.render(&document);
+        assert!(html.contains("<th>Range</th><th>Distribution</th>"));
+        assert!(html
+            .contains("<td>0 – 1</td><td><span class=\"bar\" style=\"width:30ch\"></span></td>"));
+        assert!(html.contains("style=\"width:8ch\""));
+    }
+}
@@ -0,0 +1,102 @@
+//! GitHub-flavored Markdown, the default report format.
+
+use super::document::{Block, Document, Span, Text};
+use super::Renderer;
+
+/// GitHub-flavored Markdown
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn render(&self, document: &Document) -> String {
+        let mut output = String::with_capacity(8192);
+        for block in &document.blocks {
+            render_block(&mut output, block);
+        }
+        output
+    }
+}
+
+fn render_block(output: &mut String, block: &Block) {
+    match block {
+        Block::Heading { level, text } => {
+            output.push_str(&format!("{} {}\n\n", "#".repeat(*level as usize), text));
+        }
+        Block::Paragraph(text) => {
+            output.push_str(&inline(text));
+            output.push_str("\n\n");
+        }
+        Block::Table(table) => {
+            let rows = table.rows.iter().map(|row| row.iter().map(cell).collect());
+            push_table(output, &table.headers, rows);
+        }
+        Block::KeyValue(list) => match &list.headers {
+            Some(headers) => {
+                let rows = list
+                    .rows
+                    .iter()
+                    .map(|(key, value)| vec![key.replace('|', "\\|"), cell(value)]);
+                push_table(output, headers, rows);
+            }
+            None => {
+                for (key, value) in &list.rows {
+                    output.push_str(&format!("**{}:** {}\n\n", key, inline(value)));
+                }
+            }
+        },
+        Block::BarChart(chart) => {
+            let rows = chart.bars.iter().map(|bar| {
+                let mut cells: Vec<String> =
+                    bar.cells.iter().map(|c| c.replace('|', "\\|")).collect();
+                cells.push("█".repeat(chart.width(bar.value)));
+                cells
+            });
+            push_table(output, &chart.headers, rows);
+        }
+        Block::Code { language, text } => {
+            output.push_str(&format!("```{}\n{}\n```\n\n", language, text));
+        }
+        Block::Quote(lines) => {
+            for (idx, line) in lines.iter().enumerate() {
+                output.push_str(&format!("> {}\n", inline(line)));
+                if idx + 1 < lines.len() {
+                    output.push_str(">\n");
+                }
+            }
+            output.push('\n');
+        }
+        Block::Rule => output.push_str("---\n\n"),
+        Block::Sparklines(_) => {}
+    }
+}
+
+fn push_table(output: &mut String, headers: &[String], rows: impl Iterator<Item = Vec<String>>) {
+    output.push_str(&format!("| {} |\n", headers.join(" | ")));
+    for header in headers {
+        output.push('|');
+        output.push_str(&"-".repeat(header.chars().count() + 2));
+    }
+    output.push_str("|\n");
+    for row in rows {
+        output.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+    output.push('\n');
+}
+
+fn inline(text: &Text) -> String {
+    text.0
+        .iter()
+        .map(|span| match span {
+            Span::Plain(s) => s.clone(),
+            Span::Strong(s) => format!("**{}**", s),
+            Span::Emphasis(s) => format!("*{}*", s),
+            Span::Code(s) => format!("`{}`", s),
+            Span::Status(_, text) => inline(text),
+            Span::Link { text, href } => format!("[{}]({})", inline(text), href),
+        })
+        .collect()
+}
+
+/// Inline text in a table cell, where a bare `|` would end the cell
+fn cell(text: &Text) -> String {
+    inline(text).replace('|', "\\|")
+}
@@ -0,0 +1,346 @@
+//! Plain text with aligned columns, optionally colored for terminals.
+
+use super::document::{Block, Document, Span, Status, Text};
+use super::Renderer;
+use std::io::IsTerminal;
+
+/// Plain text for files and logs: [`Terminal`] without colors
+pub struct PlainText;
+
+impl Renderer for PlainText {
+    fn render(&self, document: &Document) -> String {
+        Terminal { color: false }.render(document)
+    }
+}
+
+/// Aligned text for reading in a terminal, colored with ANSI escapes when
+/// `color` is set
+pub struct Terminal {
+    /// Use ANSI colors
+    pub color: bool,
+}
+
+/// Set `NO_COLOR` opts out of colors everywhere (https://no-color.org)
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+impl Terminal {
+    /// Colors when stdout is a terminal, unless `NO_COLOR` is set
+    pub fn for_stdout() -> Self {
+        Terminal {
+            color: !no_color() && std::io::stdout().is_terminal(),
+        }
+    }
+
+    /// Colors when stderr is a terminal, unless `NO_COLOR` is set
+    pub fn for_stderr() -> Self {
+        Terminal {
+            color: !no_color() && std::io::stderr().is_terminal(),
+        }
+    }
+
+    /// Wraps text in the escape codes turning a style on and back off
+    fn paint(&self, on: &str, off: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("\x1b[{}m{}\x1b[{}m", on, text, off)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        self.paint("1", "22", text)
+    }
+
+    fn dim(&self, text: &str) -> String {
+        self.paint("2", "22", text)
+    }
+
+    fn fg(&self, color: &str, text: &str) -> String {
+        self.paint(color, "39", text)
+    }
+
+    fn inline(&self, text: &Text) -> Cell {
+        let styled = text
+            .0
+            .iter()
+            .map(|span| match span {
+                Span::Plain(s) => s.clone(),
+                Span::Strong(s) => self.bold(s),
+                Span::Emphasis(s) => self.paint("3", "23", s),
+                Span::Code(s) => self.fg(CYAN, s),
+                Span::Status(status, text) => {
+                    let color = match status {
+                        Status::Pass => GREEN,
+                        Status::Fail => RED,
+                        Status::Unknown => YELLOW,
+                    };
+                    self.fg(color, &self.inline(text).text)
+                }
+                // Terminals show the link text only
+                Span::Link { text, .. } => self.inline(text).text,
+            })
+            .collect();
+        Cell {
+            text: styled,
+            width: width(&text.to_plain()),
+        }
+    }
+
+    fn render_block(&self, output: &mut String, block: &Block) {
+        match block {
+            Block::Heading { level, text } => {
+                let underline = match level {
+                    1 => '=',
+                    2 => '-',
+                    _ => '~',
+                };
+                output.push_str(&self.bold(text));
+                output.push('\n');
+                output.push_str(&self.dim(&underline.to_string().repeat(width(text))));
+                output.push_str("\n\n");
+            }
+            Block::Paragraph(text) => {
+                output.push_str(&self.inline(text).text);
+                output.push_str("\n\n");
+            }
+            Block::Table(table) => {
+                let rows = table
+                    .rows
+                    .iter()
+                    .map(|row| row.iter().map(|cell| self.inline(cell)).collect());
+                self.push_table(output, Some(&table.headers), rows.collect());
+            }
+            Block::KeyValue(list) => match &list.headers {
+                Some(headers) => {
+                    let rows = list
+                        .rows
+                        .iter()
+                        .map(|(key, value)| vec![Cell::plain(key), self.inline(value)]);
+                    self.push_table(output, Some(headers), rows.collect());
+                }
+                None => {
+                    let key_width = list.rows.iter().map(|(key, _)| width(key)).max();
+                    for (key, value) in &list.rows {
+                        let label = format!("{}:", key);
+                        output.push_str(&self.bold(&label));
+                        output.push_str(&pad(key_width.unwrap_or(0) + 1 - width(&label)));
+                        output.push(' ');
+                        output.push_str(&self.inline(value).text);
+                        output.push('\n');
+                    }
+                    output.push('\n');
+                }
+            },
+            Block::BarChart(chart) => {
+                let rows = chart.bars.iter().map(|bar| {
+                    let mut cells: Vec<Cell> = bar.cells.iter().map(|c| Cell::plain(c)).collect();
+                    let bar = "█".repeat(chart.width(bar.value));
+                    cells.push(Cell {
+                        width: width(&bar),
+                        text: self.fg(BLUE, &bar),
+                    });
+                    cells
+                });
+                self.push_table(output, Some(&chart.headers), rows.collect());
+            }
+            Block::Code { text, .. } => {
+                for line in text.lines() {
+                    output.push_str("    ");
+                    output.push_str(line);
+                    output.push('\n');
+                }
+                output.push('\n');
+            }
+            Block::Quote(lines) => {
+                for line in lines {
+                    output.push_str(&self.fg(YELLOW, "!"));
+                    output.push(' ');
+                    output.push_str(&self.inline(line).text);
+                    output.push('\n');
+                }
+                output.push('\n');
+            }
+            Block::Rule => {}
+            Block::Sparklines(lines) => {
+                let rows = lines.iter().map(|sparkline| {
+                    let line = sparkline.line();
+                    vec![
+                        Cell::plain(&sparkline.label),
+                        Cell {
+                            width: width(&line),
+                            text: self.fg(CYAN, &line),
+                        },
+                        Cell::plain(&sparkline.note),
+                    ]
+                });
+                self.push_table(output, None, rows.collect());
+            }
+        }
+    }
+
+    fn push_table(&self, output: &mut String, headers: Option<&[String]>, rows: Vec<Vec<Cell>>) {
+        let headers: Option<Vec<Cell>> =
+            headers.map(|headers| headers.iter().map(|h| Cell::plain(h)).collect());
+        let columns = headers
+            .as_ref()
+            .map_or(0, Vec::len)
+            .max(rows.iter().map(Vec::len).max().unwrap_or(0));
+        let mut widths = vec![0; columns];
+        for row in headers.iter().chain(&rows) {
+            for (idx, cell) in row.iter().enumerate() {
+                widths[idx] = widths[idx].max(cell.width);
+            }
+        }
+
+        let line = |cells: &[Cell], style: &dyn Fn(&str) -> String| {
+            let mut line = String::new();
+            for (idx, cell) in cells.iter().enumerate() {
+                if idx > 0 {
+                    line.push_str("  ");
+                }
+                line.push_str(&style(&cell.text));
+                if idx + 1 < cells.len() {
+                    line.push_str(&pad(widths[idx] - cell.width));
+                }
+            }
+            line.truncate(line.trim_end().len());
+            line.push('\n');
+            line
+        };
+        if let Some(headers) = &headers {
+            output.push_str(&line(headers, &|text| self.bold(text)));
+            let rules: Vec<Cell> = widths
+                .iter()
+                .map(|w| Cell::plain(&"-".repeat(*w)))
+                .collect();
+            output.push_str(&line(&rules, &|text| self.dim(text)));
+        }
+        for row in &rows {
+            output.push_str(&line(row, &|text| text.to_string()));
+        }
+        output.push('\n');
+    }
+}
+
+impl Renderer for Terminal {
+    fn render(&self, document: &Document) -> String {
+        let mut output = String::with_capacity(8192);
+        for block in &document.blocks {
+            self.render_block(&mut output, block);
+        }
+        output
+    }
+}
+
+const RED: &str = "31";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+const BLUE: &str = "34";
+const CYAN: &str = "36";
+
+/// Styled cell text with the width it takes on screen
+struct Cell {
+    text: String,
+    width: usize,
+}
+
+impl Cell {
+    fn plain(text: &str) -> Self {
+        Cell {
+            text: text.to_string(),
+            width: width(text),
+        }
+    }
+}
+
+/// Display width, counting each character as one column
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn pad(columns: usize) -> String {
+    " ".repeat(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::document::{KeyValueList, Sparkline, Status, Table, Text};
+
+    #[test]
+    fn test_text_aligns_columns() {
+        let mut document = Document::new();
+        document.heading(2, "Checks");
+        let mut table = Table::new(["Check", "Passes"]);
+        table.row(["✓ status is 200", "42"]);
+        table.row(["✗ ok", "7"]);
+        document.push(table);
+        let mut list = KeyValueList::new();
+        list.row("Test Duration", "9.50s");
+        list.row("Tag Filters", Text::code("method=GET"));
+        document.push(list);
+
+        assert_eq!(
+            PlainText.render(&document),
+            "Checks\n------\n\n\
+             Check            Passes\n\
+             ---------------  ------\n\
+             ✓ status is 200  42\n\
+             ✗ ok             7\n\n\
+             Test Duration: 9.50s\n\
+             Tag Filters:   method=GET\n\n"
+        );
+    }
+
+    #[test]
+    fn test_terminal_colors_statuses() {
+        let mut document = Document::new();
+        let mut table = Table::new(["Threshold", "Status"]);
+        table.row([
+            Text::code("p(95)<500"),
+            Text::status(Status::Fail, Text::plain("✗ ").then(Text::strong("FAIL"))),
+        ]);
+        table.row([
+            Text::code("rate<0.01"),
+            Text::status(Status::Pass, "✓ PASS"),
+        ]);
+        document.push(table);
+
+        let colored = Terminal { color: true }.render(&document);
+        assert!(colored.contains("\x1b[31m✗ \x1b[1mFAIL\x1b[22m\x1b[39m"));
+        assert!(colored.contains("\x1b[32m✓ PASS\x1b[39m"));
+        // Escapes don't count towards the column width
+        assert!(colored.contains("\x1b[36mp(95)<500\x1b[39m  \x1b[31m"));
+
+        let plain = Terminal { color: false }.render(&document);
+        assert!(!plain.contains('\x1b'));
+        assert_eq!(plain, PlainText.render(&document));
+        assert!(plain.contains("p(95)<500  ✗ FAIL\n"));
+    }
+
+    #[test]
+    fn test_sparklines() {
+        let sparkline = Sparkline {
+            label: "VUs".to_string(),
+            values: vec![Some(0.0), Some(2.0), None, Some(4.0), Some(1.0)],
+            note: "0 – 4".to_string(),
+        };
+        assert_eq!(sparkline.line(), "▁▅ █▃");
+
+        let mut document = Document::new();
+        document.push(Block::Sparklines(vec![
+            sparkline,
+            Sparkline {
+                label: "Requests/s".to_string(),
+                values: vec![Some(0.0), Some(0.0)],
+                note: "0.00 – 0.00".to_string(),
+            },
+        ]));
+        assert_eq!(
+            PlainText.render(&document),
+            "VUs         ▁▅ █▃  0 – 4\nRequests/s  ▁▁     0.00 – 0.00\n\n"
+        );
+    }
+}
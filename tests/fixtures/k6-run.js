@@ -0,0 +1,24 @@
+// The run behind the k6-run.* and k6-summary-export.json fixtures; record
+// them with record.sh
+import http from 'k6/http';
+import { check, sleep } from 'k6';
+import { Counter } from 'k6/metrics';
+
+const itemsProcessed = new Counter('items_processed');
+
+export const options = {
+    vus: 3,
+    duration: '9s',
+    summaryTrendStats: ['avg', 'min', 'med', 'max', 'p(90)', 'p(95)'],
+};
+
+export default function () {
+    const res = http.get('https://test.k6.io/');
+    check(res, { 'status is 200': (r) => r.status === 200 });
+    itemsProcessed.add(1);
+    sleep(0.5);
+}
+
+export function handleSummary(data) {
+    return { 'k6-run-summary.json': JSON.stringify(data, null, 4) };
+}
@@ -0,0 +1,12 @@
+#!/bin/sh
+# Records the fixtures from one k6 run of k6-run.js: the --out json points,
+# the --out csv rows, the handleSummary JSON and the --summary-export JSON.
+# Note the printed k6 version in test_parse_jsonl_matches_handle_summary.
+set -eu
+cd "$(dirname "$0")"
+k6 version
+k6 run \
+    --out json=k6-run.jsonl \
+    --out csv=k6-run.csv \
+    --summary-export=k6-summary-export.json \
+    k6-run.js